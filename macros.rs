@@ -0,0 +1,125 @@
+/*
+* macros.rs - Copyright (c) 2013 Letang Jeremy (letang.jeremy@gmail.com).
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+/*!
+* Macros
+*
+* The tokenizer! macro declare in one place a token enum and the StringTokenizer
+* who provide its tokens, as an alternative to the add_keyword, add_operator and
+* add_rule calls.
+*
+* The declaration provide a module with :
+* * Token - An enum with a variant for each keyword, operator and rule, and Other
+* * new - A function who create the StringTokenizer of the declaration for a string
+* * token - A function who give the variant of the current token of the StringTokenizer
+*
+* The keywords and the operators are matched as usual, a rule is added with
+* add_rule and the name of its variant. A keyword win against a rule matching
+* the same text. The other tokens ( words, numbers, special chars... ) are Other,
+* the attributes and get_token_data of the StringTokenizer still give their text
+* and their span.
+*
+* The macros of a library can't be exported to its users, this file is loaded
+* in the crate of the user with the macro_escape attribute, the name of the
+* strtokenizer crate is given to the macro.
+*
+* # Example :
+* ~~~
+*
+* extern mod strtokenizer;
+*
+* #[macro_escape]
+* #[path = "strtokenizer/macros.rs"]
+* mod macros;
+*
+* tokenizer!(mod calc use strtokenizer {
+*     keywords { Let => "let", Print => "print" }
+*     operators { EqEq => "==", Assign => "=" }
+*     rules { Int => "[0-9]+", Ident => "[a-z_]+" }
+* })
+*
+* let mut st = calc::new(~"let x = 42");
+* while st.has_token() {
+*     match calc::token(&st) {
+*         calc::Let       => io::println("LET"),
+*         calc::Ident     => io::println(fmt!("IDENT : %s", st.custom)),
+*         calc::Int       => io::println(fmt!("INT : %s", st.custom)),
+*         _               => {}
+*     }
+* }
+*
+* ~~~
+*/
+
+macro_rules! tokenizer(
+    (mod $name:ident use $library:ident {
+        keywords { $($keyword:ident => $keywordText:expr),* }
+        operators { $($operator:ident => $operatorText:expr),* }
+        rules { $($rule:ident => $pattern:expr),* }
+    }) => (
+        pub mod $name {
+            use tokenizer = $library;
+
+            // The tokens of the declaration, Other for the tokens who are not declared.
+            #[deriving(Eq, Clone)]
+            pub enum Token {
+                $($keyword,)*
+                $($operator,)*
+                $($rule,)*
+                Other
+            }
+
+            // Create the StringTokenizer of the declaration, the keywords have
+            // their variant as id.
+            pub fn new(datas : ~str) -> tokenizer::StringTokenizer {
+                let mut st = tokenizer::StringTokenizer::new_with_str(datas);
+
+                $(st.add_keyword_with_id($keywordText.to_owned(), $keyword as uint,
+                                         tokenizer::OtherKeyword);)*
+                $(st.add_operator($operatorText.to_owned());)*
+                $(match st.add_rule(stringify!($rule).to_owned(), $pattern, 0) {
+                    Ok(())      => {}
+                    Err(error)  => fail!(fmt!("invalid rule %s : %s", stringify!($rule), error))
+                })*
+                st
+            }
+
+            // Give the variant of the current token of a StringTokenizer made by new.
+            pub fn token(st : &tokenizer::StringTokenizer) -> Token {
+                match st.token {
+                    tokenizer::KeyWord  => {
+                        $(if st.keywordId == $keyword as uint { return $keyword; })*
+                    }
+                    tokenizer::Operator => {
+                        $(if st.operator == $operatorText.to_owned() { return $operator; })*
+                    }
+                    tokenizer::Custom   => {
+                        $(if st.custom == $keywordText.to_owned() { return $keyword; })*
+                        $(if st.customKind == stringify!($rule).to_owned() { return $rule; })*
+                    }
+                    _                   => {}
+                }
+                Other
+            }
+        }
+    )
+)
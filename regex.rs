@@ -0,0 +1,897 @@
+/*
+* regex.rs - Copyright (c) 2013 Letang Jeremy (letang.jeremy@gmail.com).
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+/*!
+* Regex
+*
+* A small regular expression engine used by the custom rules of StringTokenizer.
+*
+* # Supported syntax
+* * Literal chars, '.' (any char except '\n')
+* * Classes : [abc] [a-z] [^0-9], \d \w \s and their negations \D \W \S
+* * Groups and alternatives : (a|b) (?:a|b)
+* * Quantifiers : * + ? {n} {n,} {n,m}, the counts can't be greater than 1000
+* * Escapes : \n \t \r and \ followed by any other char for the char itself
+*
+* The bounded repetitions are unrolled in the automaton, a regular expression
+* can't need more than 10000 states once its repetitions are unrolled, so
+* nested repetitions like ((a{1000}){1000}){1000} are rejected.
+*
+* The matching is anchored at the given position and return the longest match.
+*
+* A RegexSet compile several regular expressions into one automaton, its
+* deterministic states are built lazily the first time they are reached,
+* then each char is read with a constant work. The fixed strings can be added
+* to a RegexSet with Regex::literal, optionally compared after case folding.
+*/
+
+use std::hashmap::HashMap;
+use std::vec;
+use scanner::fold_char;
+
+static ASCII_LEN : uint = 128;
+static UNKNOWN_STATE : uint = -1 as uint;
+static DEAD_STATE : uint = 0;
+static MAX_REPEAT : uint = 1000;
+static MAX_STATES : uint = 10000;
+
+/**
+* Private enum, an item of a char class.
+*/
+#[deriving(Clone)]
+enum ClassItem {
+    Range(char, char),
+    Digit,
+    WordChar,
+    Space,
+    NotDigit,
+    NotWordChar,
+    NotSpace
+}
+
+/**
+* Private enum, a node of a compiled regular expression.
+*/
+#[deriving(Clone)]
+enum Node {
+    Literal(char),
+    Folded(char),
+    Any,
+    Class(~[ClassItem], bool),
+    Group(~[~[Node]]),
+    Repeat(~Node, uint, Option<uint>)
+}
+
+/**
+* The Regex struct, a compiled regular expression.
+*/
+#[deriving(Clone)]
+pub struct Regex {
+    priv root : Node
+}
+
+/**
+* Private enum, a state of the non deterministic automaton of a RegexSet.
+*/
+enum NfaState {
+    Split(~[uint]),
+    Step(Node, uint),
+    Accept(uint)
+}
+
+/**
+* Private struct, a deterministic state of a RegexSet.
+*/
+struct DfaState {
+    nfaStates : ~[uint],
+    accepts : ~[uint],
+    ascii : ~[uint],
+    others : HashMap<char, uint>
+}
+
+/**
+* The RegexSet struct, several regular expressions matched in one pass.
+*/
+pub struct RegexSet {
+    priv nfa : ~[NfaState],
+    priv states : ~[DfaState],
+    priv index : HashMap<~[uint], uint>,
+    priv start : uint
+}
+
+/**
+* Private struct, the state of the regular expression parser.
+*/
+struct Parser {
+    chars : ~[char],
+    pos : uint
+}
+
+/**
+* Private function, test if a char is a word char ( letter, digit or '_' ).
+*/
+fn is_word_char(testChar : char) -> bool {
+    testChar.is_alphanumeric() || testChar == '_'
+}
+
+/**
+* Private function, test if a char match one of the items of a class.
+*/
+fn class_matches(items : &[ClassItem], testChar : char) -> bool {
+    for items.iter().advance |item| {
+        let found = match *item {
+            Range(first, last)  => testChar >= first && testChar <= last,
+            Digit               => testChar.is_digit(),
+            WordChar            => is_word_char(testChar),
+            Space               => testChar.is_whitespace(),
+            NotDigit            => !testChar.is_digit(),
+            NotWordChar         => !is_word_char(testChar),
+            NotSpace            => !testChar.is_whitespace()
+        };
+        if found {
+            return true;
+        }
+    }
+    return false
+}
+
+/**
+* Private function, test if a leaf node ( literal, any or class ) match a char.
+*/
+fn leaf_matches(node : &Node, testChar : char) -> bool {
+    match *node {
+        Literal(expected)           => testChar == expected,
+        Folded(expected)            => fold_char(testChar) == expected,
+        Any                         => testChar != '\n',
+        Class(ref items, negated)   => class_matches(*items, testChar) != negated,
+        _                           => false
+    }
+}
+
+/**
+* Private function, count the automaton states needed by a node once its
+* repetitions are unrolled, the count stop to grow above MAX_STATES.
+*/
+fn node_size(node : &Node) -> uint {
+    let size = match *node {
+        Literal(_) | Folded(_) | Any | Class(_, _)
+                                        => 1,
+        Group(ref alternatives)         => {
+            let mut size = 1;
+            for alternatives.iter().advance |alternative| {
+                for alternative.iter().advance |inner| {
+                    size += node_size(inner);
+                    if size > MAX_STATES {
+                        return size;
+                    }
+                }
+            }
+            size
+        }
+        Repeat(ref inner, min, max)     => {
+            let innerSize = node_size(*inner);
+            match max {
+                None        => (min + 1) * innerSize + 1,
+                Some(max)   => max * innerSize + max - min
+            }
+        }
+    };
+    if size > MAX_STATES { MAX_STATES + 1 } else { size }
+}
+
+/**
+* Private function, add a position to a list if it's not already in.
+*/
+fn add_position(positions : &mut ~[uint], pos : uint) -> () {
+    if !positions.contains(&pos) {
+        positions.push(pos);
+    }
+}
+
+/**
+* Private function, find all the positions where a sequence of nodes
+* can end when it begin at pos.
+*/
+fn sequence_ends(nodes : &[Node], datas : &[char], pos : uint) -> ~[uint] {
+    let mut positions = ~[pos];
+
+    for nodes.iter().advance |node| {
+        let mut next = ~[];
+        for positions.iter().advance |current| {
+            for node_ends(node, datas, *current).iter().advance |end| {
+                add_position(&mut next, *end);
+            }
+        }
+        if next.is_empty() {
+            return next;
+        }
+        positions = next;
+    }
+    positions
+}
+
+/**
+* Private function, find all the positions where a node can end when it
+* begin at pos.
+*/
+fn node_ends(node : &Node, datas : &[char], pos : uint) -> ~[uint] {
+    match *node {
+        Literal(_) | Folded(_) | Any | Class(_, _)  => {
+            if pos < datas.len() && leaf_matches(node, datas[pos]) { ~[pos + 1] } else { ~[] }
+        }
+        Group(ref alternatives)         => {
+            let mut ends = ~[];
+            for alternatives.iter().advance |alternative| {
+                for sequence_ends(*alternative, datas, pos).iter().advance |end| {
+                    add_position(&mut ends, *end);
+                }
+            }
+            ends
+        }
+        Repeat(ref inner, min, max)     => {
+            let mut ends = ~[];
+            let mut current = ~[pos];
+            let mut count = 0u;
+
+            if min == 0 {
+                ends.push(pos);
+            }
+            loop {
+                match max {
+                    Some(max) if count >= max   => break,
+                    _                           => {}
+                }
+                let mut next = ~[];
+                for current.iter().advance |from| {
+                    for node_ends(*inner, datas, *from).iter().advance |end| {
+                        // once the minimum is reached, a known position has already been expanded
+                        if count + 1 < min || !ends.contains(end) {
+                            add_position(&mut next, *end);
+                        }
+                    }
+                }
+                count += 1;
+                if next.is_empty() {
+                    break;
+                }
+                if count >= min {
+                    for next.iter().advance |end| {
+                        add_position(&mut ends, *end);
+                    }
+                }
+                current = next;
+            }
+            ends
+        }
+    }
+}
+
+/**
+* Private methods for struct Parser
+*/
+impl Parser {
+    fn is_next(&self, expected : char) -> bool {
+        self.pos < self.chars.len() && self.chars[self.pos] == expected
+    }
+
+    fn parse_alternatives(&mut self) -> Result<~[~[Node]], ~str> {
+        let mut alternatives = ~[];
+
+        loop {
+            match self.parse_sequence() {
+                Ok(sequence)    => alternatives.push(sequence),
+                Err(error)      => return Err(error)
+            }
+            if self.is_next('|') {
+                self.pos += 1;
+            }
+            else {
+                break;
+            }
+        }
+        Ok(alternatives)
+    }
+
+    fn parse_sequence(&mut self) -> Result<~[Node], ~str> {
+        let mut nodes = ~[];
+
+        while self.pos < self.chars.len() && !self.is_next('|') && !self.is_next(')') {
+            let atom = match self.parse_atom() {
+                Ok(atom)    => atom,
+                Err(error)  => return Err(error)
+            };
+            match self.parse_quantifier(atom) {
+                Ok(node)    => nodes.push(node),
+                Err(error)  => return Err(error)
+            }
+        }
+        Ok(nodes)
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, ~str> {
+        let current = self.chars[self.pos];
+
+        self.pos += 1;
+        match current {
+            '.'                         => Ok(Any),
+            '['                         => self.parse_class(),
+            '\\'                        => self.parse_escape(),
+            '('                         => {
+                if self.is_next('?') {
+                    self.pos += 1;
+                    if !self.is_next(':') {
+                        return Err(fmt!("unsupported group at %u", self.pos - 2));
+                    }
+                    self.pos += 1;
+                }
+                let alternatives = match self.parse_alternatives() {
+                    Ok(alternatives)    => alternatives,
+                    Err(error)          => return Err(error)
+                };
+                if !self.is_next(')') {
+                    return Err(~"missing ')'");
+                }
+                self.pos += 1;
+                Ok(Group(alternatives))
+            }
+            '*' | '+' | '?' | '{'       => Err(fmt!("nothing to repeat at %u", self.pos - 1)),
+            '^' | '$'                   => Err(fmt!("anchors are not supported at %u", self.pos - 1)),
+            _                           => Ok(Literal(current))
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<Node, ~str> {
+        if self.pos == self.chars.len() {
+            return Err(~"trailing '\\'");
+        }
+        let escaped = self.chars[self.pos];
+        self.pos += 1;
+        match escaped {
+            'd'     => Ok(Class(~[Digit], false)),
+            'D'     => Ok(Class(~[Digit], true)),
+            'w'     => Ok(Class(~[WordChar], false)),
+            'W'     => Ok(Class(~[WordChar], true)),
+            's'     => Ok(Class(~[Space], false)),
+            'S'     => Ok(Class(~[Space], true)),
+            'n'     => Ok(Literal('\n')),
+            't'     => Ok(Literal('\t')),
+            'r'     => Ok(Literal('\r')),
+            _       => Ok(Literal(escaped))
+        }
+    }
+
+    fn parse_class_char(&mut self) -> Result<ClassItem, ~str> {
+        let current = self.chars[self.pos];
+
+        self.pos += 1;
+        if current != '\\' {
+            return Ok(Range(current, current));
+        }
+        if self.pos == self.chars.len() {
+            return Err(~"missing ']'");
+        }
+        let escaped = self.chars[self.pos];
+        self.pos += 1;
+        match escaped {
+            'd'     => Ok(Digit),
+            'D'     => Ok(NotDigit),
+            'w'     => Ok(WordChar),
+            'W'     => Ok(NotWordChar),
+            's'     => Ok(Space),
+            'S'     => Ok(NotSpace),
+            'n'     => Ok(Range('\n', '\n')),
+            't'     => Ok(Range('\t', '\t')),
+            'r'     => Ok(Range('\r', '\r')),
+            _       => Ok(Range(escaped, escaped))
+        }
+    }
+
+    fn parse_class(&mut self) -> Result<Node, ~str> {
+        let mut items = ~[];
+        let mut negated = false;
+        let begin = self.pos - 1;
+
+        if self.is_next('^') {
+            negated = true;
+            self.pos += 1;
+        }
+        while self.pos < self.chars.len() && (!self.is_next(']') || items.is_empty()) {
+            let item = match self.parse_class_char() {
+                Ok(item)    => item,
+                Err(error)  => return Err(error)
+            };
+            match item {
+                Range(first, _) if self.is_next('-')
+                    && self.pos + 1 < self.chars.len()
+                    && self.chars[self.pos + 1] != ']'    => {
+                    self.pos += 1;
+                    match self.parse_class_char() {
+                        Ok(Range(last, _)) if last >= first => items.push(Range(first, last)),
+                        Ok(_)                               => {
+                            return Err(fmt!("invalid range in class at %u", begin));
+                        }
+                        Err(error)                          => return Err(error)
+                    }
+                }
+                _                                           => items.push(item)
+            }
+        }
+        if !self.is_next(']') {
+            return Err(fmt!("missing ']' for class at %u", begin));
+        }
+        self.pos += 1;
+        Ok(Class(items, negated))
+    }
+
+    fn parse_number(&mut self) -> Option<uint> {
+        let mut number = 0u;
+        let mut digits = 0;
+
+        while self.pos < self.chars.len() && self.chars[self.pos].is_digit() {
+            if number <= MAX_REPEAT {
+                number = number * 10 + self.chars[self.pos].to_digit(10).unwrap();
+            }
+            digits += 1;
+            self.pos += 1;
+        }
+        if digits == 0 { None } else { Some(number) }
+    }
+
+    fn parse_quantifier(&mut self, atom : Node) -> Result<Node, ~str> {
+        if self.is_next('*') {
+            self.pos += 1;
+            return Ok(Repeat(~atom, 0, None));
+        }
+        else if self.is_next('+') {
+            self.pos += 1;
+            return Ok(Repeat(~atom, 1, None));
+        }
+        else if self.is_next('?') {
+            self.pos += 1;
+            return Ok(Repeat(~atom, 0, Some(1)));
+        }
+        else if self.is_next('{') {
+            let begin = self.pos;
+            self.pos += 1;
+            let min = match self.parse_number() {
+                Some(min)   => min,
+                None        => return Err(fmt!("invalid repetition at %u", begin))
+            };
+            let mut max = Some(min);
+            if self.is_next(',') {
+                self.pos += 1;
+                max = self.parse_number();
+            }
+            if !self.is_next('}') {
+                return Err(fmt!("invalid repetition at %u", begin));
+            }
+            self.pos += 1;
+            match max {
+                Some(max) if max < min  => Err(fmt!("invalid repetition at %u", begin)),
+                Some(max) if max > MAX_REPEAT
+                                        => Err(fmt!("repetition count too large at %u", begin)),
+                _ if min > MAX_REPEAT   => Err(fmt!("repetition count too large at %u", begin)),
+                _                       => Ok(Repeat(~atom, min, max))
+            }
+        }
+        else {
+            Ok(atom)
+        }
+    }
+}
+
+/**
+* Methods for struct Regex
+*/
+impl Regex {
+    /**
+    * Compile a new regular expression.
+    *
+    * # Arguments
+    * * pattern - The regular expression
+    *
+    * Return the compiled Regex, or a message describing the syntax error.
+    */
+    pub fn new(pattern : &str) -> Result<Regex, ~str> {
+        let mut chars = ~[];
+        for pattern.iter().advance |pchar| {
+            chars.push(pchar);
+        }
+        let mut parser = Parser {
+            chars : chars,
+            pos : 0
+        };
+        let alternatives = match parser.parse_alternatives() {
+            Ok(alternatives)    => alternatives,
+            Err(error)          => return Err(error)
+        };
+        if parser.pos != parser.chars.len() {
+            return Err(fmt!("unbalanced ')' at %u", parser.pos));
+        }
+        let root = Group(alternatives);
+        if node_size(&root) > MAX_STATES {
+            return Err(~"regular expression too large once its repetitions are unrolled");
+        }
+        Ok(Regex { root : root })
+    }
+
+    /**
+    * Create a regular expression matching a fixed string.
+    *
+    * # Arguments
+    * * text - The string to match, the chars have no special meaning
+    * * folded - true if the chars are compared after case folding ( see scanner::fold_char )
+    *
+    * Return the new Regex.
+    */
+    pub fn literal(text : &str, folded : bool) -> Regex {
+        let mut sequence = ~[];
+
+        for text.iter().advance |tchar| {
+            sequence.push(if folded { Folded(fold_char(tchar)) } else { Literal(tchar) });
+        }
+        Regex { root : Group(~[sequence]) }
+    }
+
+    /**
+    * Match the regular expression at a position.
+    *
+    * # Arguments
+    * * datas - The chars to match
+    * * pos - The position where the match must begin
+    *
+    * Return the end of the longest match, None if the regex doesn't match.
+    */
+    pub fn match_at(&self, datas : &[char], pos : uint) -> Option<uint> {
+        let ends = node_ends(&self.root, datas, pos);
+        let mut longest = None;
+
+        for ends.iter().advance |end| {
+            match longest {
+                Some(current) if current >= *end    => {}
+                _                                   => longest = Some(*end)
+            }
+        }
+        longest
+    }
+}
+
+/**
+* Methods for struct RegexSet
+*/
+impl RegexSet {
+    /**
+    * Compile several regular expressions into one automaton.
+    *
+    * # Arguments
+    * * regexs - The regular expressions, their index identify them in the matches
+    *
+    * Return the new RegexSet.
+    */
+    pub fn new(regexs : &[Regex]) -> RegexSet {
+        let mut set = RegexSet {
+            nfa : ~[],
+            states : ~[],
+            index : HashMap::new(),
+            start : DEAD_STATE
+        };
+        let mut starts = ~[];
+
+        for regexs.iter().enumerate().advance |(i, regex)| {
+            let accept = set.push_state(Accept(i));
+            starts.push(set.compile_node(&regex.root, accept));
+        }
+        let nfaStart = set.push_state(Split(starts));
+        set.add_dfa_state(~[]);
+        let first = set.closure([nfaStart]);
+        set.start = set.add_dfa_state(first);
+        set
+    }
+
+    /**
+    * Private function, add a state to the non deterministic automaton.
+    */
+    fn push_state(&mut self, state : NfaState) -> uint {
+        self.nfa.push(state);
+        self.nfa.len() - 1
+    }
+
+    /**
+    * Private function, compile a node into states going to next once matched.
+    *
+    * Return the first state of the node.
+    */
+    fn compile_node(&mut self, node : &Node, next : uint) -> uint {
+        match *node {
+            Literal(_) | Folded(_) | Any | Class(_, _)
+                                            => self.push_state(Step(node.clone(), next)),
+            Group(ref alternatives)         => {
+                let mut starts = ~[];
+                for alternatives.iter().advance |alternative| {
+                    starts.push(self.compile_sequence(*alternative, next));
+                }
+                self.push_state(Split(starts))
+            }
+            Repeat(ref inner, min, max)     => {
+                let mut start = next;
+                match max {
+                    None        => {
+                        let split = self.push_state(Split(~[]));
+                        let body = self.compile_node(*inner, split);
+                        self.nfa[split] = Split(~[body, next]);
+                        start = split;
+                    }
+                    Some(max)   => {
+                        for (max - min).times {
+                            let body = self.compile_node(*inner, start);
+                            start = self.push_state(Split(~[body, next]));
+                        }
+                    }
+                }
+                for min.times {
+                    start = self.compile_node(*inner, start);
+                }
+                start
+            }
+        }
+    }
+
+    /**
+    * Private function, compile a sequence of nodes into states going to next once matched.
+    *
+    * Return the first state of the sequence.
+    */
+    fn compile_sequence(&mut self, nodes : &[Node], next : uint) -> uint {
+        let mut start = next;
+        let mut i = nodes.len();
+
+        while i > 0 {
+            i -= 1;
+            start = self.compile_node(&nodes[i], start);
+        }
+        start
+    }
+
+    /**
+    * Private function, follow the epsilon transitions of a set of states.
+    *
+    * Return the sorted list of the reached states who read a char or accept.
+    */
+    fn closure(&self, states : &[uint]) -> ~[uint] {
+        let mut visited = vec::from_elem(self.nfa.len(), false);
+        let mut stack = states.to_owned();
+        let mut closure = ~[];
+
+        while !stack.is_empty() {
+            let state = stack.pop();
+            if !visited[state] {
+                visited[state] = true;
+                match self.nfa[state] {
+                    Split(ref nexts)    => {
+                        for nexts.iter().advance |next| {
+                            stack.push(*next);
+                        }
+                    }
+                    _                   => {}
+                }
+            }
+        }
+        for self.nfa.iter().enumerate().advance |(state, nfaState)| {
+            match *nfaState {
+                Split(_)    => {}
+                _           => if visited[state] { closure.push(state) }
+            }
+        }
+        closure
+    }
+
+    /**
+    * Private function, find or create the deterministic state of a set of states.
+    */
+    fn add_dfa_state(&mut self, nfaStates : ~[uint]) -> uint {
+        let known = match self.index.find(&nfaStates) {
+            Some(state) => Some(*state),
+            None        => None
+        };
+        if known.is_some() {
+            return known.unwrap();
+        }
+        let mut accepts = ~[];
+        for nfaStates.iter().advance |state| {
+            match self.nfa[*state] {
+                Accept(regex)   => accepts.push(regex),
+                _               => {}
+            }
+        }
+        self.states.push(DfaState {
+            nfaStates : nfaStates.clone(),
+            accepts : accepts,
+            ascii : vec::from_elem(ASCII_LEN, UNKNOWN_STATE),
+            others : HashMap::new()
+        });
+        self.index.insert(nfaStates, self.states.len() - 1);
+        self.states.len() - 1
+    }
+
+    /**
+    * Private function, get the transition of a deterministic state for a char,
+    * the transition is computed the first time.
+    */
+    fn transition(&mut self, state : uint, nextChar : char) -> uint {
+        let cached = if (nextChar as uint) < ASCII_LEN {
+            self.states[state].ascii[nextChar as uint]
+        }
+        else {
+            match self.states[state].others.find(&nextChar) {
+                Some(next)  => *next,
+                None        => UNKNOWN_STATE
+            }
+        };
+        if cached != UNKNOWN_STATE {
+            return cached;
+        }
+        let mut targets = ~[];
+        for self.states[state].nfaStates.iter().advance |nfaState| {
+            match self.nfa[*nfaState] {
+                Step(ref node, next) if leaf_matches(node, nextChar)   => targets.push(next),
+                _                                                       => {}
+            }
+        }
+        let closure = self.closure(targets);
+        let next = self.add_dfa_state(closure);
+        if (nextChar as uint) < ASCII_LEN {
+            self.states[state].ascii[nextChar as uint] = next;
+        }
+        else {
+            self.states[state].others.insert(nextChar, next);
+        }
+        next
+    }
+
+    /**
+    * Find all the non empty matches of the regular expressions at a position,
+    * the chars are read once until no regular expression can match.
+    *
+    * # Arguments
+    * * datas - The chars to match
+    * * pos - The position where the matches must begin
+    *
+    * Return the end of each match with the deterministic state reached there,
+    * the regular expressions matching are given by accepts.
+    */
+    pub fn matches(&mut self, datas : &[char], pos : uint) -> ~[(uint, uint)] {
+        let mut state = self.start;
+        let mut i = pos;
+        let mut found = ~[];
+
+        while i < datas.len() && state != DEAD_STATE {
+            state = self.transition(state, datas[i]);
+            i += 1;
+            if !self.states[state].accepts.is_empty() {
+                found.push((i, state));
+            }
+        }
+        found
+    }
+
+    /**
+    * Get the regular expressions accepted by a deterministic state returned by matches.
+    *
+    * Return the sorted indexes of the regular expressions.
+    */
+    pub fn accepts<'r>(&'r self, state : uint) -> &'r [uint] {
+        let accepts : &'r [uint] = self.states[state].accepts;
+        accepts
+    }
+
+    /**
+    * Find the longest non empty match of the regular expressions at a position.
+    *
+    * # Arguments
+    * * datas - The chars to match
+    * * pos - The position where the match must begin
+    *
+    * Return the end of the longest match and the index of all the regular
+    * expressions matching it, None if nothing match.
+    */
+    pub fn longest_match(&mut self, datas : &[char], pos : uint) -> Option<(uint, ~[uint])> {
+        let mut state = self.start;
+        let mut i = pos;
+        let mut found = None;
+
+        while i < datas.len() && state != DEAD_STATE {
+            state = self.transition(state, datas[i]);
+            i += 1;
+            if !self.states[state].accepts.is_empty() {
+                found = Some((i, self.states[state].accepts.clone()));
+            }
+        }
+        found
+    }
+}
+
+#[cfg(test)]
+fn match_str(pattern : &str, datas : &str) -> Option<uint> {
+    let mut chars = ~[];
+    for datas.iter().advance |dchar| {
+        chars.push(dchar);
+    }
+    Regex::new(pattern).unwrap().match_at(chars, 0)
+}
+
+#[test]
+fn test_regex() -> () {
+    assert_eq!(match_str("#[0-9a-f]{6}", "#ff00aa;"), Some(7));
+    assert_eq!(match_str("#[0-9a-f]{6}", "#ff00a"), None);
+    assert_eq!(match_str("v\\d+(\\.\\d+){2}", "v1.22.3.4"), Some(7));
+    assert_eq!(match_str("a|ab|abc", "abcd"), Some(3));
+    assert_eq!(match_str("(a*)*b", "aaab"), Some(4));
+    assert_eq!(match_str("[^ ]*", "foo bar"), Some(3));
+    assert_eq!(match_str("x?", "abc"), Some(0));
+    assert!(Regex::new("(a").is_err());
+    assert!(Regex::new("a)").is_err());
+    assert!(Regex::new("*a").is_err());
+    assert!(Regex::new("[z-a]").is_err());
+    assert!(Regex::new("a{3,2}").is_err());
+    assert!(Regex::new("a{1000}").is_ok());
+    assert!(Regex::new("a{1001}").is_err());
+    assert!(Regex::new("a{2,100000}").is_err());
+    assert!(Regex::new("a{99999999999999999999999}").is_err());
+    assert!(Regex::new("(a{10}b){100}").is_ok());
+    assert!(Regex::new("(a{100}){1000}").is_err());
+    assert!(Regex::new("((a{1000}){1000}){1000}").is_err());
+    assert!(Regex::new("(((((a{2,1000}){1000}){1000}){1000}){1000})*").is_err());
+}
+
+#[test]
+fn test_literal() -> () {
+    let chars = ['S', 'e', 'L', 'e', 'C', 't', '(', '.', '*', ')'];
+
+    assert_eq!(Regex::literal("select", true).match_at(chars, 0), Some(6));
+    assert_eq!(Regex::literal("select", false).match_at(chars, 0), None);
+    assert_eq!(Regex::literal("(.*)", false).match_at(chars, 6), Some(10));
+    assert_eq!(Regex::literal("(.*)", false).match_at(chars, 5), None);
+}
+
+#[test]
+fn test_regex_set() -> () {
+    let regexs = ~[Regex::new("[0-9]+").unwrap(),
+                   Regex::new("[0-9]+(\\.[0-9]+)?").unwrap(),
+                   Regex::new("0x[0-9a-f]+").unwrap(),
+                   Regex::new("(ab)*").unwrap()];
+    let mut set = RegexSet::new(regexs);
+    let datas = ['1', '2', '.', '5', ' ', '0', 'x', 'f', 'a', 'b', 'a', 'b'];
+
+    assert_eq!(set.longest_match(datas, 0), Some((4, ~[1])));
+    assert_eq!(set.longest_match(datas, 3), Some((4, ~[0, 1])));
+    assert_eq!(set.longest_match(datas, 5), Some((12, ~[2])));
+    assert_eq!(set.longest_match(datas, 8), Some((12, ~[3])));
+    assert_eq!(set.longest_match(datas, 9), None);
+    assert_eq!(set.longest_match(datas, 4), None);
+
+    let mut found = ~[];
+    for set.matches(datas, 0).iter().advance |&(end, state)| {
+        found.push((end, set.accepts(state).to_owned()));
+    }
+    assert_eq!(found, ~[(1, ~[0, 1]), (2, ~[0, 1]), (4, ~[1])]);
+    assert!(set.matches(datas, 4).is_empty());
+}
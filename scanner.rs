@@ -0,0 +1,437 @@
+/*
+* scanner.rs - Copyright (c) 2013 Letang Jeremy (letang.jeremy@gmail.com).
+*
+* This software is provided 'as-is', without any express or implied warranty.
+* In no event will the authors be held liable for any damages arising from
+* the use of this software.
+*
+* Permission is granted to anyone to use this software for any purpose,
+* including commercial applications, and to alter it and redistribute it
+* freely, subject to the following restrictions:
+*
+* 1. The origin of this software must not be misrepresented; you must not claim
+*    that you wrote the original software. If you use this software in a product,
+*    an acknowledgment in the product documentation would be appreciated but is
+*    not required.
+*
+* 2. Altered source versions must be plainly marked as such, and must not be
+*    misrepresented as being the original software.
+*
+* 3. This notice may not be removed or altered from any source distribution.
+*/
+
+/*!
+* Scanner
+*
+* The tables compiled from the settings of a StringTokenizer :
+* * CharTable - The class of each char ( delimiter, special char, quote )
+* * Lexicon - One deterministic automaton recognizing the comments markers,
+*   the operators, the keywords and the custom rules
+*
+* Both are looked up with a constant work per char, ASCII chars use a
+* direct table and the others chars a hash map. The Lexicon read the chars
+* once from the begin of a token and keep all its matches, so each kind of
+* token is found without reading the chars again.
+*
+* The case folding used to compare the keywords without regard to the case
+* is also defined here.
+*/
+
+use std::hashmap::HashMap;
+use std::vec;
+use regex::{Regex, RegexSet};
+
+pub static DELIMITER : u8 = 1;
+pub static SPECIAL_CHAR : u8 = 2;
+pub static QUOTE_CHAR : u8 = 4;
+
+static ASCII_LEN : uint = 128;
+
+/**
+* The CharTable struct, the flags of each char.
+*/
+pub struct CharTable {
+    priv ascii : ~[u8],
+    priv others : HashMap<char, u8>
+}
+
+/**
+* The Pattern enum, what is recognized by a string or a rule of a Lexicon.
+*
+* Each variant contain an index choosen by the owner of the Lexicon.
+*/
+#[deriving(Eq, Clone)]
+pub enum Pattern {
+    CommentMarker(uint),
+    FixedOperator(uint),
+    FixedKeyword(uint),
+    CustomPattern(uint)
+}
+
+/**
+* The Lexicon struct, a deterministic automaton recognizing fixed strings and
+* regular expressions.
+*
+* The automaton is run once from a position, its matches are kept until an
+* other position is scanned.
+*/
+pub struct Lexicon {
+    priv patterns : ~[Pattern],
+    priv regexs : ~[Regex],
+    priv automaton : RegexSet,
+    priv scanned : Option<uint>,
+    priv found : ~[(uint, uint)]
+}
+
+/**
+* Methods for struct CharTable
+*/
+impl CharTable {
+    /**
+    * Create a new CharTable without any flag.
+    */
+    pub fn new() -> CharTable {
+        CharTable {
+            ascii : vec::from_elem(ASCII_LEN, 0u8),
+            others : HashMap::new()
+        }
+    }
+
+    /**
+    * Add a flag to a char.
+    *
+    * # Arguments
+    * * flagChar - The char to update
+    * * flag - The flag to add ( DELIMITER / SPECIAL_CHAR / QUOTE_CHAR )
+    */
+    pub fn add(&mut self, flagChar : char, flag : u8) -> () {
+        if (flagChar as uint) < ASCII_LEN {
+            self.ascii[flagChar as uint] |= flag;
+        }
+        else {
+            let flags = match self.others.find(&flagChar) {
+                Some(flags) => *flags,
+                None        => 0u8
+            };
+            self.others.insert(flagChar, flags | flag);
+        }
+    }
+
+    /**
+    * Test if a char has a flag.
+    *
+    * Return true if the char has the flag, false otherwise
+    */
+    pub fn has(&self, testChar : char, flag : u8) -> bool {
+        if (testChar as uint) < ASCII_LEN {
+            (self.ascii[testChar as uint] & flag) != 0
+        }
+        else {
+            match self.others.find(&testChar) {
+                Some(flags) => (*flags & flag) != 0,
+                None        => false
+            }
+        }
+    }
+}
+
+/**
+* Methods for struct Lexicon
+*/
+impl Lexicon {
+    /**
+    * Create a new Lexicon recognizing nothing.
+    */
+    pub fn new() -> Lexicon {
+        Lexicon {
+            patterns : ~[],
+            regexs : ~[],
+            automaton : RegexSet::new([]),
+            scanned : None,
+            found : ~[]
+        }
+    }
+
+    /**
+    * Add a fixed string to the Lexicon, build must be called before the next scan.
+    *
+    * # Arguments
+    * * text - The string to recognize, empty strings are ignored
+    * * folded - true if the chars are compared after case folding
+    * * pattern - The pattern returned when the string is recognized
+    */
+    pub fn add_literal(&mut self, text : &str, folded : bool, pattern : Pattern) -> () {
+        if text.len() > 0 {
+            self.add_regex(Regex::literal(text, folded), pattern);
+        }
+    }
+
+    /**
+    * Add a regular expression to the Lexicon, build must be called before the next scan.
+    *
+    * # Arguments
+    * * regex - The regular expression to recognize
+    * * pattern - The pattern returned when the regular expression match
+    */
+    pub fn add_regex(&mut self, regex : Regex, pattern : Pattern) -> () {
+        self.regexs.push(regex);
+        self.patterns.push(pattern);
+    }
+
+    /**
+    * Compile the strings and the regular expressions into the automaton.
+    */
+    pub fn build(&mut self) -> () {
+        self.automaton = RegexSet::new(self.regexs);
+        self.forget();
+    }
+
+    /**
+    * Forget the matches of the last scan, to call when the chars are changed.
+    */
+    pub fn forget(&mut self) -> () {
+        self.scanned = None;
+        self.found.clear();
+    }
+
+    /**
+    * Run the automaton from a position, nothing is done if it is the position
+    * of the last scan.
+    *
+    * # Arguments
+    * * datas - The chars to read
+    * * pos - The position where the matches must begin
+    */
+    pub fn scan(&mut self, datas : &[char], pos : uint) -> () {
+        if self.scanned != Some(pos) {
+            self.found = self.automaton.matches(datas, pos);
+            self.scanned = Some(pos);
+        }
+    }
+
+    /**
+    * Private function, get the accepted patterns of a match of the last scan.
+    */
+    fn patterns_of(&self, state : uint, accepted : &fn(Pattern) -> bool) -> ~[Pattern] {
+        let mut patterns = ~[];
+
+        for self.automaton.accepts(state).iter().advance |index| {
+            if accepted(self.patterns[*index]) {
+                patterns.push(self.patterns[*index]);
+            }
+        }
+        patterns
+    }
+
+    /**
+    * Find the longest match of the last scan.
+    *
+    * # Arguments
+    * * accepted - Select the patterns who can be returned
+    *
+    * Return the end of the match and its patterns in the order they were
+    * added, None if nothing is recognized.
+    */
+    pub fn longest(&self, accepted : &fn(Pattern) -> bool) -> Option<(uint, ~[Pattern])> {
+        let mut i = self.found.len();
+
+        while i > 0 {
+            i -= 1;
+            let (end, state) = self.found[i];
+            let patterns = self.patterns_of(state, |pattern| accepted(pattern));
+            if !patterns.is_empty() {
+                return Some((end, patterns));
+            }
+        }
+        None
+    }
+
+    /**
+    * Find the patterns of the last scan matching exactly until a position.
+    *
+    * # Arguments
+    * * end - The end of the match
+    * * accepted - Select the patterns who can be returned
+    *
+    * Return the patterns in the order they were added.
+    */
+    pub fn ending_at(&self, end : uint, accepted : &fn(Pattern) -> bool) -> ~[Pattern] {
+        for self.found.iter().advance |&(matchEnd, state)| {
+            if matchEnd == end {
+                return self.patterns_of(state, |pattern| accepted(pattern));
+            }
+        }
+        ~[]
+    }
+}
+
+/**
+* The simple case folding of Unicode 14.0 ( CaseFolding.txt, status C and S ).
+*
+* Each range ( first, last, delta, step ) fold the chars from first to last
+* who are a multiple of step away from first by adding delta to them.
+*/
+static FOLD_RANGES : &'static [(uint, uint, int, uint)] = &[
+    (0x0041, 0x005A, 32, 1), (0x00B5, 0x00B5, 775, 1), (0x00C0, 0x00D6, 32, 1),
+    (0x00D8, 0x00DE, 32, 1), (0x0100, 0x012E, 1, 2), (0x0132, 0x0136, 1, 2),
+    (0x0139, 0x0147, 1, 2), (0x014A, 0x0176, 1, 2), (0x0178, 0x0178, -121, 1),
+    (0x0179, 0x017D, 1, 2), (0x017F, 0x017F, -268, 1), (0x0181, 0x0181, 210, 1),
+    (0x0182, 0x0184, 1, 2), (0x0186, 0x0186, 206, 1), (0x0187, 0x0187, 1, 1),
+    (0x0189, 0x018A, 205, 1), (0x018B, 0x018B, 1, 1), (0x018E, 0x018E, 79, 1),
+    (0x018F, 0x018F, 202, 1), (0x0190, 0x0190, 203, 1), (0x0191, 0x0191, 1, 1),
+    (0x0193, 0x0193, 205, 1), (0x0194, 0x0194, 207, 1), (0x0196, 0x0196, 211, 1),
+    (0x0197, 0x0197, 209, 1), (0x0198, 0x0198, 1, 1), (0x019C, 0x019C, 211, 1),
+    (0x019D, 0x019D, 213, 1), (0x019F, 0x019F, 214, 1), (0x01A0, 0x01A4, 1, 2),
+    (0x01A6, 0x01A6, 218, 1), (0x01A7, 0x01A7, 1, 1), (0x01A9, 0x01A9, 218, 1),
+    (0x01AC, 0x01AC, 1, 1), (0x01AE, 0x01AE, 218, 1), (0x01AF, 0x01AF, 1, 1),
+    (0x01B1, 0x01B2, 217, 1), (0x01B3, 0x01B5, 1, 2), (0x01B7, 0x01B7, 219, 1),
+    (0x01B8, 0x01B8, 1, 1), (0x01BC, 0x01BC, 1, 1), (0x01C4, 0x01C4, 2, 1),
+    (0x01C5, 0x01C5, 1, 1), (0x01C7, 0x01C7, 2, 1), (0x01C8, 0x01C8, 1, 1),
+    (0x01CA, 0x01CA, 2, 1), (0x01CB, 0x01DB, 1, 2), (0x01DE, 0x01EE, 1, 2),
+    (0x01F1, 0x01F1, 2, 1), (0x01F2, 0x01F4, 1, 2), (0x01F6, 0x01F6, -97, 1),
+    (0x01F7, 0x01F7, -56, 1), (0x01F8, 0x021E, 1, 2), (0x0220, 0x0220, -130, 1),
+    (0x0222, 0x0232, 1, 2), (0x023A, 0x023A, 10795, 1), (0x023B, 0x023B, 1, 1),
+    (0x023D, 0x023D, -163, 1), (0x023E, 0x023E, 10792, 1), (0x0241, 0x0241, 1, 1),
+    (0x0243, 0x0243, -195, 1), (0x0244, 0x0244, 69, 1), (0x0245, 0x0245, 71, 1),
+    (0x0246, 0x024E, 1, 2), (0x0345, 0x0345, 116, 1), (0x0370, 0x0372, 1, 2),
+    (0x0376, 0x0376, 1, 1), (0x037F, 0x037F, 116, 1), (0x0386, 0x0386, 38, 1),
+    (0x0388, 0x038A, 37, 1), (0x038C, 0x038C, 64, 1), (0x038E, 0x038F, 63, 1),
+    (0x0391, 0x03A1, 32, 1), (0x03A3, 0x03AB, 32, 1), (0x03C2, 0x03C2, 1, 1),
+    (0x03CF, 0x03CF, 8, 1), (0x03D0, 0x03D0, -30, 1), (0x03D1, 0x03D1, -25, 1),
+    (0x03D5, 0x03D5, -15, 1), (0x03D6, 0x03D6, -22, 1), (0x03D8, 0x03EE, 1, 2),
+    (0x03F0, 0x03F0, -54, 1), (0x03F1, 0x03F1, -48, 1), (0x03F4, 0x03F4, -60, 1),
+    (0x03F5, 0x03F5, -64, 1), (0x03F7, 0x03F7, 1, 1), (0x03F9, 0x03F9, -7, 1),
+    (0x03FA, 0x03FA, 1, 1), (0x03FD, 0x03FF, -130, 1), (0x0400, 0x040F, 80, 1),
+    (0x0410, 0x042F, 32, 1), (0x0460, 0x0480, 1, 2), (0x048A, 0x04BE, 1, 2),
+    (0x04C0, 0x04C0, 15, 1), (0x04C1, 0x04CD, 1, 2), (0x04D0, 0x052E, 1, 2),
+    (0x0531, 0x0556, 48, 1), (0x10A0, 0x10C5, 7264, 1), (0x10C7, 0x10C7, 7264, 1),
+    (0x10CD, 0x10CD, 7264, 1), (0x13F8, 0x13FD, -8, 1), (0x1C80, 0x1C80, -6222, 1),
+    (0x1C81, 0x1C81, -6221, 1), (0x1C82, 0x1C82, -6212, 1), (0x1C83, 0x1C84, -6210, 1),
+    (0x1C85, 0x1C85, -6211, 1), (0x1C86, 0x1C86, -6204, 1), (0x1C87, 0x1C87, -6180, 1),
+    (0x1C88, 0x1C88, 35267, 1), (0x1C90, 0x1CBA, -3008, 1), (0x1CBD, 0x1CBF, -3008, 1),
+    (0x1E00, 0x1E94, 1, 2), (0x1E9B, 0x1E9B, -58, 1), (0x1E9E, 0x1E9E, -7615, 1),
+    (0x1EA0, 0x1EFE, 1, 2), (0x1F08, 0x1F0F, -8, 1), (0x1F18, 0x1F1D, -8, 1),
+    (0x1F28, 0x1F2F, -8, 1), (0x1F38, 0x1F3F, -8, 1), (0x1F48, 0x1F4D, -8, 1),
+    (0x1F59, 0x1F5F, -8, 2), (0x1F68, 0x1F6F, -8, 1), (0x1F88, 0x1F8F, -8, 1),
+    (0x1F98, 0x1F9F, -8, 1), (0x1FA8, 0x1FAF, -8, 1), (0x1FB8, 0x1FB9, -8, 1),
+    (0x1FBA, 0x1FBB, -74, 1), (0x1FBC, 0x1FBC, -9, 1), (0x1FBE, 0x1FBE, -7173, 1),
+    (0x1FC8, 0x1FCB, -86, 1), (0x1FCC, 0x1FCC, -9, 1), (0x1FD8, 0x1FD9, -8, 1),
+    (0x1FDA, 0x1FDB, -100, 1), (0x1FE8, 0x1FE9, -8, 1), (0x1FEA, 0x1FEB, -112, 1),
+    (0x1FEC, 0x1FEC, -7, 1), (0x1FF8, 0x1FF9, -128, 1), (0x1FFA, 0x1FFB, -126, 1),
+    (0x1FFC, 0x1FFC, -9, 1), (0x2126, 0x2126, -7517, 1), (0x212A, 0x212A, -8383, 1),
+    (0x212B, 0x212B, -8262, 1), (0x2132, 0x2132, 28, 1), (0x2160, 0x216F, 16, 1),
+    (0x2183, 0x2183, 1, 1), (0x24B6, 0x24CF, 26, 1), (0x2C00, 0x2C2F, 48, 1),
+    (0x2C60, 0x2C60, 1, 1), (0x2C62, 0x2C62, -10743, 1), (0x2C63, 0x2C63, -3814, 1),
+    (0x2C64, 0x2C64, -10727, 1), (0x2C67, 0x2C6B, 1, 2), (0x2C6D, 0x2C6D, -10780, 1),
+    (0x2C6E, 0x2C6E, -10749, 1), (0x2C6F, 0x2C6F, -10783, 1), (0x2C70, 0x2C70, -10782, 1),
+    (0x2C72, 0x2C72, 1, 1), (0x2C75, 0x2C75, 1, 1), (0x2C7E, 0x2C7F, -10815, 1),
+    (0x2C80, 0x2CE2, 1, 2), (0x2CEB, 0x2CED, 1, 2), (0x2CF2, 0x2CF2, 1, 1),
+    (0xA640, 0xA66C, 1, 2), (0xA680, 0xA69A, 1, 2), (0xA722, 0xA72E, 1, 2),
+    (0xA732, 0xA76E, 1, 2), (0xA779, 0xA77B, 1, 2), (0xA77D, 0xA77D, -35332, 1),
+    (0xA77E, 0xA786, 1, 2), (0xA78B, 0xA78B, 1, 1), (0xA78D, 0xA78D, -42280, 1),
+    (0xA790, 0xA792, 1, 2), (0xA796, 0xA7A8, 1, 2), (0xA7AA, 0xA7AA, -42308, 1),
+    (0xA7AB, 0xA7AB, -42319, 1), (0xA7AC, 0xA7AC, -42315, 1), (0xA7AD, 0xA7AD, -42305, 1),
+    (0xA7AE, 0xA7AE, -42308, 1), (0xA7B0, 0xA7B0, -42258, 1), (0xA7B1, 0xA7B1, -42282, 1),
+    (0xA7B2, 0xA7B2, -42261, 1), (0xA7B3, 0xA7B3, 928, 1), (0xA7B4, 0xA7C2, 1, 2),
+    (0xA7C4, 0xA7C4, -48, 1), (0xA7C5, 0xA7C5, -42307, 1), (0xA7C6, 0xA7C6, -35384, 1),
+    (0xA7C7, 0xA7C9, 1, 2), (0xA7D0, 0xA7D0, 1, 1), (0xA7D6, 0xA7D8, 1, 2),
+    (0xA7F5, 0xA7F5, 1, 1), (0xAB70, 0xABBF, -38864, 1), (0xFF21, 0xFF3A, 32, 1),
+    (0x10400, 0x10427, 40, 1), (0x104B0, 0x104D3, 40, 1), (0x10570, 0x1057A, 39, 1),
+    (0x1057C, 0x1058A, 39, 1), (0x1058C, 0x10592, 39, 1), (0x10594, 0x10595, 39, 1),
+    (0x10C80, 0x10CB2, 64, 1), (0x118A0, 0x118BF, 32, 1), (0x16E40, 0x16E5F, 32, 1),
+    (0x1E900, 0x1E921, 34, 1)
+];
+
+/**
+* Fold the case of a char.
+*
+* The simple case folding of Unicode is applied, the chars who only have a
+* full case folding ( ß / İ / ŉ ) and the chars without case are kept.
+*
+* Return the folded char.
+*/
+pub fn fold_char(foldChar : char) -> char {
+    let code = foldChar as uint;
+    let mut low = 0;
+    let mut high = FOLD_RANGES.len();
+
+    while low < high {
+        let middle = (low + high) / 2;
+        let (first, last, delta, step) = FOLD_RANGES[middle];
+        if code < first {
+            high = middle;
+        }
+        else if code > last {
+            low = middle + 1;
+        }
+        else if (code - first) % step == 0 {
+            return ((code as int + delta) as uint) as char;
+        }
+        else {
+            return foldChar;
+        }
+    }
+    foldChar
+}
+
+/**
+* Fold the case of a string, see fold_char.
+*
+* Return the folded string.
+*/
+pub fn fold_str(string : &str) -> ~str {
+    let mut folded = ~"";
+
+    for string.iter().advance |schar| {
+        folded.push_char(fold_char(schar));
+    }
+    folded
+}
+
+#[test]
+fn test_fold_case() -> () {
+    assert_eq!(fold_str("SeLeCt"), ~"select");
+    assert_eq!(fold_str("ÀÉ×ÞŸ"), ~"àé×þÿ");
+    assert_eq!(fold_str("ĀĹŊŹ"), ~"āĺŋź");
+    assert_eq!(fold_str("ΣΟΦΙΑ σοφός"), ~"σοφια σοφόσ");
+    assert_eq!(fold_str("ЁЖИК"), ~"ёжик");
+    assert_eq!(fold_str("İß€"), ~"İß€");
+    assert_eq!(fold_str("ΆΈΏ ϐϑϕ"), ~"άέώ βθφ");
+    assert_eq!(fold_str("ƁǄǅȺɊ"), ~"ɓǆǆⱥɋ");
+    assert_eq!(fold_str("ԱՐՄ"), ~"արմ");
+    assert_eq!(fold_str("ѠҐӁԀ"), ~"ѡґӂԁ");
+    assert_eq!(fold_str("ẞµſKÅ"), ~"ßμskå");
+    assert_eq!(fold_str("ႠᲐꭰ𐐀"), ~"ⴀაᎠ𐐨");
+}
+
+#[test]
+fn test_lexicon() -> () {
+    let mut lexicon = Lexicon::new();
+    let datas = ['-', '>', '>', '=', ' ', 'I', 'f', '1', '2'];
+
+    lexicon.add_literal("-", false, FixedOperator(0));
+    lexicon.add_literal("->", false, FixedOperator(1));
+    lexicon.add_literal("->>=", false, FixedOperator(2));
+    lexicon.add_literal("->", false, FixedOperator(3));
+    lexicon.add_literal("", false, CommentMarker(0));
+    lexicon.add_literal("if", true, FixedKeyword(0));
+    lexicon.add_literal("if", false, FixedKeyword(1));
+    lexicon.add_regex(Regex::new("[a-zA-Z][a-zA-Z0-9]*").unwrap(), CustomPattern(0));
+    lexicon.build();
+
+    lexicon.scan(datas, 0);
+    assert_eq!(lexicon.longest(|_| true), Some((4, ~[FixedOperator(2)])));
+    assert_eq!(lexicon.ending_at(2, |pattern| pattern != CustomPattern(0)),
+               ~[FixedOperator(1), FixedOperator(3)]);
+    assert_eq!(lexicon.longest(|pattern| pattern != FixedOperator(2)),
+               Some((2, ~[FixedOperator(1), FixedOperator(3)])));
+    assert_eq!(lexicon.ending_at(3, |_| true), ~[]);
+
+    lexicon.scan(datas, 5);
+    assert_eq!(lexicon.ending_at(7, |_| true), ~[FixedKeyword(0), CustomPattern(0)]);
+    assert_eq!(lexicon.longest(|_| true), Some((9, ~[CustomPattern(0)])));
+    assert_eq!(lexicon.longest(|pattern| pattern == FixedOperator(0)), None);
+
+    lexicon.scan(datas, 1);
+    assert_eq!(lexicon.longest(|_| true), None);
+}
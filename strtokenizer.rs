@@ -34,11 +34,46 @@
 *         strtokenizer::Word          => io::println(fmt!("WORD : %s", st.word)),
 *         strtokenizer::Number        => io::println(fmt!("NUMBER : %s", st.number)),
 *         strtokenizer::KeyWord       => io::println(fmt!("KEYWORD : %s", st.keyword)),
+*         strtokenizer::Operator      => io::println(fmt!("OPERATOR : %s", st.operator)),
+*         strtokenizer::Custom        => io::println(fmt!("%s : %s", st.customKind, st.custom)),
+*         strtokenizer::StrStart | strtokenizer::StrFragment | strtokenizer::InterpStart
+*         | strtokenizer::InterpEnd | strtokenizer::StrEnd
+*                                     => io::println(fmt!("STRING PART : %s", st.stringPart)),
+*         strtokenizer::Indent        => io::println("INDENT"),
+*         strtokenizer::Dedent        => io::println("DEDENT"),
+*         strtokenizer::IndentError   => io::println(fmt!("INDENT ERROR : %s", st.indentError)),
+*         strtokenizer::Newline       => io::println("NEW LINE"),
+*         strtokenizer::EndOfFile     => io::println("END OF FILE"),
+*         strtokenizer::Bool          => io::println(fmt!("BOOL : %b", st.boolean)),
+*         strtokenizer::Null          => io::println(fmt!("NULL : %s", st.literal)),
+*         strtokenizer::Dimension     => io::println(fmt!("DIMENSION : %s %s", st.number, st.unit)),
+*         strtokenizer::Version       => io::println(fmt!("VERSION : %s", st.dotted)),
+*         strtokenizer::IpAddress     => io::println(fmt!("IP ADDRESS : %s", st.dotted)),
+*         strtokenizer::DottedNumber  => io::println(fmt!("DOTTED NUMBER : %s", st.dotted)),
+*         strtokenizer::Date          => io::println(fmt!("DATE : %s", st.dateTime)),
+*         strtokenizer::Time          => io::println(fmt!("TIME : %s", st.dateTime)),
+*         strtokenizer::DateTime      => io::println(fmt!("DATE TIME : %s", st.dateTime)),
+*         strtokenizer::Duration      => io::println(fmt!("DURATION : %s ns", st.duration.to_str())),
+*         strtokenizer::Url           => io::println(fmt!("URL : %s", st.location)),
+*         strtokenizer::Email         => io::println(fmt!("EMAIL : %s", st.location)),
+*         strtokenizer::Path          => io::println(fmt!("PATH : %s", st.location)),
+*         strtokenizer::Sigiled       => io::println(fmt!("SIGILED : %c %s", st.sigil, st.name)),
 *         strtokenizer::NoToken       => {}
 *        }
 *    }
 *
 * ~~~
+*
+* # Profile Example :
+* ~~~
+*
+* let mut st = strtokenizer::StringTokenizer::new_with_profile(~"int main() { return 0; }",
+*                                                              strtokenizer::CProfile);
+*
+* ~~~
+*
+* A token enum and its StringTokenizer can also be declared together with the
+* tokenizer! macro ( see the macros module ).
 */
 
 #[link(name = "strtokenizer",
@@ -47,12 +82,43 @@
        uuid = "5CB40369-7C8F-4D70-A419-1FEA19AAF46D")];
 #[crate_type = "lib"];
 
+extern mod extra;
+
 #[cfg(test)]
 use std::io;
 #[cfg(test)]
 use std::path::PosixPath;
+#[cfg(test)]
+use std::uint;
+use std::int;
+#[cfg(test)]
+use extra::test::BenchHarness;
 
 use std::str;
+use std::util;
+use std::vec;
+use extra::json;
+use regex::Regex;
+use scanner::{CharTable, Lexicon, CommentMarker, FixedOperator, FixedKeyword, CustomPattern};
+use scanner::{DELIMITER, SPECIAL_CHAR, QUOTE_CHAR, fold_str};
+
+#[macro_escape]
+mod macros;
+pub mod regex;
+mod scanner;
+
+static C_COMMENT_TAG : uint = 0;
+static CPP_COMMENT_TAG : uint = 1;
+static MULTI_COMMENT_TAG : uint = 2;
+static SINGLE_COMMENT_TAG : uint = 3;
+
+static DEFAULT_MODE : &'static str = "default";
+static SOFT_KEYWORD_HISTORY : uint = 4;
+#[cfg(date_times)]
+static DURATION_UNITS : [(&'static str, u64), ..7] = [
+    ("ms", 1000000), ("us", 1000), ("ns", 1), ("d", 86400000000000),
+    ("h", 3600000000000), ("m", 60000000000), ("s", 1000000000)
+];
 
 /**
 * The CommentsTypes enum, define the differents comments types availables.
@@ -64,43 +130,581 @@ pub enum CommentsTypes {
     NoComments
 }
 
+/**
+* The Profile enum, define the differents languages profiles availables.
+*
+* A profile preconfigure the keywords, operators, comments, strings
+* and numbers rules of a StringTokenizer for a given language.
+*/
+pub enum Profile {
+    CProfile,
+    RustProfile,
+    PythonProfile,
+    JavaScriptProfile,
+    SqlProfile,
+    ShellProfile,
+    JsonProfile,
+    CssProfile,
+    LispProfile
+}
+
+/**
+* The KeywordCategory enum, define the differents kinds of keywords.
+*/
+#[deriving(Eq)]
+pub enum KeywordCategory {
+    TypeKeyword,
+    ControlKeyword,
+    LiteralKeyword,
+    ContextualKeyword,
+    OtherKeyword
+}
+
 /**
 * The Token enum, define the differents token availables from StringTokenizer.
 */
+#[deriving(Eq)]
 pub enum Token {
     Word,
     KeyWord,
     Number,
     SpecialChar,
+    Operator,
+    Custom,
+    StrStart,
+    StrFragment,
+    InterpStart,
+    InterpEnd,
+    StrEnd,
+    Indent,
+    Dedent,
+    IndentError,
+    Newline,
+    EndOfFile,
+    Bool,
+    Null,
+    Dimension,
+    Version,
+    IpAddress,
+    DottedNumber,
+    Date,
+    Time,
+    DateTime,
+    Duration,
+    Url,
+    Email,
+    Path,
+    Sigiled,
     NoToken
 }
 
+/**
+* The ModeTransition enum, define the change of lexer mode triggered by a token.
+*/
+#[deriving(Clone)]
+pub enum ModeTransition {
+    PushMode(~str),
+    PopMode
+}
+
+/**
+* Private struct, a user defined rule matched by a regular expression.
+*/
+#[deriving(Clone)]
+struct CustomRule {
+    name : ~str,
+    pattern : ~str,
+    regex : Regex,
+    priority : int
+}
+
+/**
+* Private struct, the description of an interpolated string literal.
+*/
+#[deriving(Clone)]
+struct InterpolatedString {
+    prefix : ~str,
+    quote : char,
+    begin : ~str,
+    end : ~str,
+    open : ~str
+}
+
+/**
+* Private struct, an interpolated string being read.
+*
+* The depth count the brackets opened in the expression who are not closed yet.
+*/
+#[deriving(Clone)]
+struct Interpolation {
+    string : InterpolatedString,
+    quotes : uint,
+    inString : bool,
+    depth : uint
+}
+
+/**
+* Private struct, the dictionnary of a lexer mode who is not the current one.
+*/
+struct ModeSettings {
+    keyWords : ~[~str],
+    keywordKinds : ~[(uint, KeywordCategory)],
+    caseInsensitiveKeywords : bool,
+    literals : ~[(~str, Option<bool>)],
+    delimiters : ~[char],
+    specialChars : ~[char],
+    operators : ~[~str],
+    quoteChars : ~[char],
+    hexNumbers : bool,
+    exponentNumbers : bool,
+    signedNumbers : bool,
+    versions : bool,
+    ipAddresses : bool,
+    dottedNumbers : bool,
+    dateTimes : bool,
+    urls : bool,
+    emails : bool,
+    paths : bool,
+    digitSeparator : Option<char>,
+    units : ~[~str],
+    sigils : ~[char],
+    numberedSigils : ~[char],
+    identifierChars : ~[char],
+    rules : ~[CustomRule],
+    interpolatedStrings : ~[InterpolatedString],
+    offsideRule : bool,
+    comments : CommentsTypes,
+    returnIsToken : bool,
+    unicodeNewLines : bool,
+    collapseBlankLines : bool,
+    ignoreEscapeChar : bool,
+    stringPrefixes : ~[~str],
+    tripleQuotes : bool,
+    doubledQuotes : bool,
+    nestedComments : bool,
+    multiCommentBegin : ~str,
+    multiCommentEnd : ~str,
+    singleComment : ~str,
+    compiled : bool,
+    charTable : CharTable,
+    lexicon : Lexicon
+}
+
+/**
+* Private struct, a named lexer mode.
+*
+* The settings of the current mode are stored in the StringTokenizer itself.
+*/
+#[deriving(Clone)]
+struct LexerMode {
+    name : ~str,
+    settings : Option<ModeSettings>,
+    transitions : ~[(~str, ModeTransition)]
+}
+
+/**
+* The Span struct, the position of a token in the datas of a StringTokenizer.
+*
+* # Public attributes
+* * begin - The position of the first char of the token
+* * end   - The position after the last char of the token
+*/
+#[deriving(Clone, Eq)]
+pub struct Span {
+    begin : uint,
+    end : uint
+}
+
+/**
+* The Timestamp struct, the components of a Date, Time or DateTime token.
+*
+* The components who are not in the token are 0.
+*
+* # Public attributes
+* * year / month / day - The date
+* * hour / minute / second / nanosecond - The time
+* * offset - The offset from UTC in minutes ( Z is Some(0) ), None if there is no offset
+*/
+#[deriving(Clone, Eq)]
+pub struct Timestamp {
+    year : uint,
+    month : uint,
+    day : uint,
+    hour : uint,
+    minute : uint,
+    second : uint,
+    nanosecond : uint,
+    offset : Option<int>
+}
+
+/**
+* The TokenData struct, a copy of a token read by a StringTokenizer.
+*
+* # Public attributes
+* * token          - The type of the token
+* * text           - The text of the token
+* * leadingTrivia  - The delimiters and comments before the token in lossless mode
+* * trailingTrivia - The delimiters and comments after the token in lossless mode
+* * span           - The position of the token in the datas
+* * mode           - The name of the lexer mode used to read the token
+*/
+#[deriving(Clone)]
+pub struct TokenData {
+    token : Token,
+    text : ~str,
+    leadingTrivia : ~str,
+    trailingTrivia : ~str,
+    span : Span,
+    mode : ~str
+}
+
 /**
 * The StringTokenizer struct.
 *
 * # Public attributes
 * * token       - Token enum, define the type of the current token
-* * number      - Contain the current token if the type of the token is Number
+* * number      - Contain the current token if the type of the token is Number,
+*   the value if the type of the token is Dimension
+* * unit        - The unit of the current token if the type of the token is Dimension
+* * dotted      - Contain the current token if the type of the token is Version, IpAddress
+*   or DottedNumber
+* * dateTime    - Contain the current token if the type of the token is Date, Time, DateTime
+*   or Duration
+* * timestamp   - The components of the current token if the type of the token is Date,
+*   Time or DateTime
+* * duration    - The number of nanoseconds if the type of the token is Duration
+* * location    - Contain the current token if the type of the token is Url, Email or Path
+* * sigil       - The sigil of the current token if the type of the token is Sigiled
+* * name        - The identifier of the current token if the type of the token is Sigiled
 * * word        - Contain the current token if the type of the token is Word
+* * keywordId   - The id of the keyword if the type of the token is KeyWord
+* * keywordCategory - The category of the keyword if the type of the token is KeyWord
+* * maybeKeyword - true if the type of the token is Word and the word is a soft keyword
+* * literal     - Contain the current token if the type of the token is Bool or Null
+* * boolean     - The value of the current token if the type of the token is Bool
 * * specialChar - Contain the current token if the type of the token is specialChar
+* * operator    - Contain the current token if the type of the token is Operator
+* * custom      - Contain the current token if the type of the token is Custom
+* * customKind  - Contain the name of the rule who matched if the type of the token is Custom
+* * mode        - The name of the lexer mode used to read the current token
+* * stringPart  - Contain the current token if the type of the token is StrStart,
+*                 StrFragment, InterpStart, InterpEnd or StrEnd
+* * indentation - The indentation of the line if the type of the token is Indent or Dedent
+* * indentError - The error message if the type of the token is IndentError
+* * newline     - Contain the current token if the type of the token is Newline
+* * span        - The position of the current token in the datas
+* * leadingTrivia  - The delimiters and comments before the current token in lossless mode
+* * trailingTrivia - The delimiters and comments after the current token on the same
+*                    line in lossless mode
 */
 pub struct StringTokenizer {
     priv datas : ~[char],
+    priv source : ~[char],
+    priv physical : ~[uint],
+    priv lineContinuation : Option<~str>,
+    priv lossless : bool,
+    priv triviaEnd : uint,
+    priv endOfDatas : bool,
     priv pos : uint,
+    priv tokenBegin : uint,
     priv keyWords : ~[~str], 
+    priv keywordKinds : ~[(uint, KeywordCategory)],
+    priv literals : ~[(~str, Option<bool>)],
+    priv caseInsensitiveKeywords : bool,
     priv delimiters : ~[char],
     priv specialChars : ~[char],
+    priv operators : ~[~str],
+    priv quoteChars : ~[char],
+    priv hexNumbers : bool,
+    priv exponentNumbers : bool,
+    priv signedNumbers : bool,
+    priv versions : bool,
+    priv ipAddresses : bool,
+    priv dottedNumbers : bool,
+    priv dateTimes : bool,
+    priv urls : bool,
+    priv emails : bool,
+    priv paths : bool,
+    priv digitSeparator : Option<char>,
+    priv units : ~[~str],
+    priv sigils : ~[char],
+    priv numberedSigils : ~[char],
+    priv identifierChars : ~[char],
+    priv rules : ~[CustomRule],
+    priv interpolatedStrings : ~[InterpolatedString],
+    priv offsideRule : bool,
     priv comments : CommentsTypes,
     priv returnIsToken : bool,
+    priv unicodeNewLines : bool,
+    priv collapseBlankLines : bool,
     priv ignoreEscapeChar : bool,
+    priv stringPrefixes : ~[~str],
+    priv tripleQuotes : bool,
+    priv doubledQuotes : bool,
+    priv nestedComments : bool,
     priv multiCommentBegin : ~str,
     priv multiCommentEnd : ~str,
     priv singleComment : ~str,
+    priv compiled : bool,
+    priv charTable : CharTable,
+    priv lexicon : Lexicon,
+    priv modes : ~[LexerMode],
+    priv currentMode : uint,
+    priv modeStack : ~[uint],
+    priv interpolations : ~[Interpolation],
+    priv bracketPairs : ~[(char, char)],
+    priv softKeywordPredicate : Option<@fn(&str, &[TokenData]) -> bool>,
+    priv history : ~[TokenData],
+    priv indentStack : ~[~str],
+    priv pendingDedents : uint,
+    priv indentLine : Option<uint>,
+    priv bracketDepth : uint,
+    priv lastToken : Token,
+    priv lastSpecialChar : char,
+    priv lastTokenEnd : uint,
     token : Token,
     number : ~str,
+    unit : ~str,
+    dotted : ~str,
+    dateTime : ~str,
+    timestamp : Timestamp,
+    duration : u64,
+    location : ~str,
+    sigil : char,
+    name : ~str,
     word : ~str,
     keyword : ~str,
-    specialChar : char
+    keywordId : uint,
+    keywordCategory : KeywordCategory,
+    maybeKeyword : bool,
+    literal : ~str,
+    boolean : bool,
+    specialChar : char,
+    operator : ~str,
+    custom : ~str,
+    customKind : ~str,
+    mode : ~str,
+    stringPart : ~str,
+    indentation : ~str,
+    indentError : ~str,
+    newline : ~str,
+    span : Span,
+    leadingTrivia : ~str,
+    trailingTrivia : ~str
+}
+
+/**
+* The TokenTree enum, a token or a group of tokens between brackets.
+*/
+pub enum TokenTree {
+    Leaf(TokenData),
+    Group(TokenGroup)
+}
+
+/**
+* The TokenGroup struct, the tokens between two brackets.
+*
+* # Public attributes
+* * delimiter - The char who open the group
+* * openSpan  - The position of the opening bracket
+* * closeSpan - The position of the closing bracket
+* * children  - The tokens and the groups between the brackets
+*/
+pub struct TokenGroup {
+    delimiter : char,
+    openSpan : Span,
+    closeSpan : Span,
+    children : ~[TokenTree]
+}
+
+/**
+* The BracketError struct, describe an unmatched or a mismatched bracket.
+*
+* # Public attributes
+* * message   - The description of the error
+* * openSpan  - The position of the opening bracket, None for an unmatched closing bracket
+* * closeSpan - The position of the closing bracket, None for an unmatched opening bracket
+*/
+pub struct BracketError {
+    message : ~str,
+    openSpan : Option<Span>,
+    closeSpan : Option<Span>
+}
+
+/**
+* The SettingsError struct, describe why a settings document can't be loaded.
+*
+* # Public attributes
+* * key     - The key of the faulty setting, empty if the error is not related to a key
+* * message - The description of the error
+*/
+pub struct SettingsError {
+    key : ~str,
+    message : ~str
+}
+
+/**
+* Private enum, a value read from or written to a settings document.
+*/
+enum SettingValue {
+    StringSetting(~str),
+    BoolSetting(bool),
+    ListSetting(~[~str])
+}
+
+/**
+* Private function, create a new SettingsError.
+*/
+fn settings_error(key : &str, message : &str) -> SettingsError {
+    SettingsError {
+        key : key.to_owned(),
+        message : message.to_owned()
+    }
+}
+
+/**
+* Private function, convert a list of chars to a list of one char strings.
+*/
+fn chars_to_settings(chars : &[char]) -> ~[~str] {
+    let mut strs = ~[];
+
+    for chars.iter().advance |schar| {
+        strs.push(str::from_char(*schar));
+    }
+    strs
+}
+
+/**
+* Private function, quote and escape a string for a TOML or JSON document.
+*/
+fn quote_setting(value : &str) -> ~str {
+    let mut quoted = ~"\"";
+
+    for value.iter().advance |schar| {
+        match schar {
+            '"'     => quoted.push_str("\\\""),
+            '\\'    => quoted.push_str("\\\\"),
+            '\n'    => quoted.push_str("\\n"),
+            '\r'    => quoted.push_str("\\r"),
+            '\t'    => quoted.push_str("\\t"),
+            _       => quoted.push_char(schar)
+        }
+    }
+    quoted.push_char('"');
+    quoted
+}
+
+/**
+* Private function, remove the quotes of a string read in a TOML document.
+*
+* Escape sequences are only handled in double quoted strings.
+*
+* Return the content of the string, None if the word is not a quoted string.
+*/
+fn unquote_setting(word : &str) -> Option<~str> {
+    let chars = str_to_vec(word.to_owned());
+    let len = chars.len();
+    let mut value = ~"";
+    let mut i = 1;
+
+    if len < 2 || chars[0] != chars[len - 1]
+        || (chars[0] != '"' && chars[0] != '\'') {
+        return None;
+    }
+    if chars[0] == '\'' {
+        return Some(str::from_chars(chars.slice(1, len - 1)));
+    }
+    while i < len - 1 {
+        if chars[i] == '\\' && i + 1 < len - 1 {
+            i += 1;
+            match chars[i] {
+                'n'     => value.push_char('\n'),
+                'r'     => value.push_char('\r'),
+                't'     => value.push_char('\t'),
+                other   => value.push_char(other)
+            }
+        }
+        else {
+            value.push_char(chars[i]);
+        }
+        i += 1;
+    }
+    Some(value)
+}
+
+/**
+* Private function, find a profile from its name in a settings document.
+*/
+fn profile_from_name(name : &str) -> Option<Profile> {
+    match name {
+        "c"             => Some(CProfile),
+        "rust"          => Some(RustProfile),
+        "python"        => Some(PythonProfile),
+        "javascript"    => Some(JavaScriptProfile),
+        "sql"           => Some(SqlProfile),
+        "shell"         => Some(ShellProfile),
+        "json"          => Some(JsonProfile),
+        "css"           => Some(CssProfile),
+        "lisp"          => Some(LispProfile),
+        _               => None
+    }
+}
+
+/**
+* Private methods for enum SettingValue, check the type of a value.
+*/
+impl SettingValue {
+    fn to_document(&self) -> ~str {
+        match *self {
+            StringSetting(ref value)    => quote_setting(*value),
+            BoolSetting(value)          => if value { ~"true" } else { ~"false" },
+            ListSetting(ref values)     => {
+                let mut quoted = ~[];
+                for values.iter().advance |value| {
+                    quoted.push(quote_setting(*value));
+                }
+                fmt!("[%s]", quoted.connect(", "))
+            }
+        }
+    }
+
+    fn get_string(&self, key : &str) -> Result<~str, SettingsError> {
+        match *self {
+            StringSetting(ref value)    => Ok(value.clone()),
+            _                           => Err(settings_error(key, "expected a string"))
+        }
+    }
+
+    fn get_bool(&self, key : &str) -> Result<bool, SettingsError> {
+        match *self {
+            BoolSetting(value)  => Ok(value),
+            _                   => Err(settings_error(key, "expected a boolean"))
+        }
+    }
+
+    fn get_list(&self, key : &str) -> Result<~[~str], SettingsError> {
+        match *self {
+            ListSetting(ref values) => Ok(values.clone()),
+            _                       => Err(settings_error(key, "expected a list of strings"))
+        }
+    }
+
+    fn get_chars(&self, key : &str) -> Result<~[char], SettingsError> {
+        let mut chars = ~[];
+
+        match *self {
+            ListSetting(ref values) => {
+                for values.iter().advance |value| {
+                    if value.char_len() != 1 {
+                        return Err(settings_error(key, "expected a list of one char strings"));
+                    }
+                    chars.push(value.char_at(0));
+                }
+                Ok(chars)
+            }
+            _                       => Err(settings_error(key, "expected a list of one char strings"))
+        }
+    }
 }
 
 /**
@@ -109,14 +713,80 @@ pub struct StringTokenizer {
 * Return the owned vector of chars.
 */
 pub fn str_to_vec(string : ~str) -> ~[char]{
-    let mut bytes = ~[];
-    let mut i = 0;
+    let mut chars = ~[];
+
+    for string.iter().advance |schar| {
+        chars.push(schar);
+    }
+    chars
+}
+
+/**
+* Private function, create a Timestamp without any component.
+*/
+fn new_timestamp() -> Timestamp {
+    Timestamp {
+        year : 0,
+        month : 0,
+        day : 0,
+        hour : 0,
+        minute : 0,
+        second : 0,
+        nanosecond : 0,
+        offset : None
+    }
+}
+
+/**
+* Private function, test if a char is an ASCII letter.
+*/
+fn is_ascii_letter(testChar : char) -> bool {
+    (testChar >= 'a' && testChar <= 'z') || (testChar >= 'A' && testChar <= 'Z')
+}
+
+/**
+* Private function, test if a char is an ASCII letter, digit or '_'.
+*/
+fn is_identifier_char(testChar : char) -> bool {
+    is_ascii_letter(testChar) || testChar.is_digit() || testChar == '_'
+}
+
+/**
+* Private function, test if a char is an ASCII letter, digit or '-' ( the chars of
+* the identifiers of a version and of the labels of a domain ).
+*/
+fn is_label_char(testChar : char) -> bool {
+    is_ascii_letter(testChar) || testChar.is_digit() || testChar == '-'
+}
+
+/**
+* Private function, count the groups of 1 to 4 hexadecimal digits separated by ':'.
+*
+* Return the number of groups, None if a group is empty or too long
+*/
+fn hex_groups(chars : &[char]) -> Option<uint> {
+    let mut groups = 0;
+    let mut size = 0;
 
-    while i < string.len() {
-        bytes.push(string.char_at(i));
-        i+= 1;
+    if chars.len() == 0 {
+        return Some(0);
+    }
+    for chars.iter().advance |schar| {
+        if *schar == ':' {
+            if size == 0 {
+                return None;
+            }
+            groups += 1;
+            size = 0;
+        }
+        else if size == 4 || schar.to_digit(16).is_none() {
+            return None;
+        }
+        else {
+            size += 1;
+        }
     }
-    bytes
+    if size == 0 { None } else { Some(groups + 1) }
 }
 
 /**
@@ -133,23 +803,105 @@ impl StringTokenizer {
     */
     pub fn new(datas : ~[char]) -> StringTokenizer {
         let mut st = StringTokenizer {
-            datas : datas,
+            datas : ~[],
+            source : ~[],
+            physical : ~[],
+            lineContinuation : None,
+            lossless : false,
+            triviaEnd : 0,
+            endOfDatas : false,
             pos : 0,
+            tokenBegin : 0,
             keyWords : ~[],
+            keywordKinds : ~[],
+            literals : ~[],
+            caseInsensitiveKeywords : false,
             delimiters : ~[],
             specialChars : ~[],
+            operators : ~[],
+            quoteChars : ~['"', '\''],
+            hexNumbers : false,
+            exponentNumbers : false,
+            signedNumbers : false,
+            versions : false,
+            ipAddresses : false,
+            dottedNumbers : false,
+            dateTimes : false,
+            urls : false,
+            emails : false,
+            paths : false,
+            digitSeparator : None,
+            units : ~[],
+            sigils : ~[],
+            numberedSigils : ~[],
+            identifierChars : ~[],
+            rules : ~[],
+            interpolatedStrings : ~[],
+            offsideRule : false,
             comments : NoComments,
             returnIsToken : true,
+            unicodeNewLines : false,
+            collapseBlankLines : false,
             ignoreEscapeChar : false,
+            stringPrefixes : ~[],
+            tripleQuotes : false,
+            doubledQuotes : false,
+            nestedComments : false,
             multiCommentBegin : ~"",
             multiCommentEnd : ~"",
             singleComment : ~"",
+            compiled : false,
+            charTable : CharTable::new(),
+            lexicon : Lexicon::new(),
+            modes : ~[LexerMode {
+                name : DEFAULT_MODE.to_owned(),
+                settings : None,
+                transitions : ~[]
+            }],
+            currentMode : 0,
+            modeStack : ~[],
+            interpolations : ~[],
+            bracketPairs : ~[('(', ')'), ('[', ']'), ('{', '}')],
+            softKeywordPredicate : None,
+            history : ~[],
+            indentStack : ~[],
+            pendingDedents : 0,
+            indentLine : None,
+            bracketDepth : 0,
+            lastToken : NoToken,
+            lastSpecialChar : 0 as char,
+            lastTokenEnd : 0,
             token : NoToken,
             number : ~"",
+            unit : ~"",
+            dotted : ~"",
+            dateTime : ~"",
+            timestamp : new_timestamp(),
+            duration : 0,
+            location : ~"",
+            sigil : 0 as char,
+            name : ~"",
             word : ~"",
             keyword : ~"",
-            specialChar : 0 as char
+            keywordId : 0,
+            keywordCategory : OtherKeyword,
+            maybeKeyword : false,
+            literal : ~"",
+            boolean : false,
+            specialChar : 0 as char,
+            operator : ~"",
+            custom : ~"",
+            customKind : ~"",
+            mode : DEFAULT_MODE.to_owned(),
+            stringPart : ~"",
+            indentation : ~"",
+            indentError : ~"",
+            newline : ~"",
+            span : Span { begin : 0, end : 0 },
+            leadingTrivia : ~"",
+            trailingTrivia : ~""
         };
+        st.set_datas(datas);
         st.initialize();
         st
     }
@@ -163,25 +915,21 @@ impl StringTokenizer {
     * Return a new instance of StringTokenizer.
     */
     pub fn new_with_str(datas : ~str) -> StringTokenizer {
-        let mut st = StringTokenizer {
-            datas : str_to_vec(datas),
-            pos : 0,
-            keyWords : ~[],
-            delimiters : ~[],
-            specialChars : ~[],
-            comments : NoComments,
-            returnIsToken : true,
-            ignoreEscapeChar : false,
-            multiCommentBegin : ~"",
-            multiCommentEnd : ~"",
-            singleComment : ~"",
-            token : NoToken,
-            number : ~"",
-            word : ~"",
-            keyword : ~"",
-            specialChar : 0 as char
-        };
-        st.initialize();
+        StringTokenizer::new(str_to_vec(datas))
+    }
+
+    /**
+    * Create a new StringTokenizer object configured for a language.
+    *
+    * # Arguments
+    * * datas - The string to tokenize
+    * * profile - The language profile to load
+    *
+    * Return a new instance of StringTokenizer.
+    */
+    pub fn new_with_profile(datas : ~str, profile : Profile) -> StringTokenizer {
+        let mut st = StringTokenizer::new_with_str(datas);
+        st.set_profile(profile);
         st
     }
     
@@ -213,6 +961,7 @@ impl StringTokenizer {
         self.specialChars.push('?');
         self.specialChars.push(':');
         self.specialChars.push(',');
+        self.compile();
         self.consume_delimiters();
     }
     
@@ -220,73 +969,427 @@ impl StringTokenizer {
     * Private function consume char while there is delimiters.
     */
     fn consume_delimiters(&mut self) -> () {
-        while self.pos < self.datas.len() 
-            && self.is_delimiter(self.datas[self.pos]) { 
+        while self.pos < self.datas.len() {
+            let lineBreak = self.line_break_len(self.pos) > 0;
+            if (lineBreak && self.returnIsToken && !(self.offsideRule && self.bracketDepth > 0))
+                || (!lineBreak && !self.is_delimiter(self.datas[self.pos])) {
+                break;
+            }
             self.pos += 1;
         }
     }
 
     /**
-    * Private function test if a char is a delimiter or not
+    * Private function, test if a char end a line.
     *
     * # Arguments
     * * testChar - The character to test
     *
-    * Return true if the char is a delimiter, false otherwise
+    * Return true if the char is '\n', '\r' or U+2028 when it is allowed, false otherwise
     */
-    fn is_delimiter(&mut self, testChar : char) -> bool {
-        for self.delimiters.iter().advance |delim| {
-            if *delim == testChar {
-                return true;
-            }
-        }
-        return false
+    fn is_line_break(&self, testChar : char) -> bool {
+        testChar == '\n' || testChar == '\r' || (self.unicodeNewLines && testChar == '\u2028')
     }
 
     /**
-    * Private function, test if a char is a special char.
-    *
-    * # Arguments
-    * * testChar - The character to test
+    * Private function, compute the length of the line break at a position.
     *
-    * Return true if the char is a special char, false otherwise
+    * Return the number of chars of the line break, 0 if there is no line break
     */
-    fn is_special_char(&mut self, testChar : char) -> bool {
-        for self.specialChars.iter().advance |spec| {
-            if *spec == testChar {
-                return true;
-            }
+    fn line_break_len(&self, pos : uint) -> uint {
+        if pos >= self.datas.len() || !self.is_line_break(self.datas[pos]) {
+            0
+        }
+        else if self.datas[pos] == '\r' && pos + 1 < self.datas.len()
+            && self.datas[pos + 1] == '\n' {
+            2
+        }
+        else {
+            1
         }
-        return false
     }
 
     /**
-    * Update the vector of data to tokenize by a new one.
+    * Private function test if a char is a delimiter or not
+    *
+    * # Arguments
+    * * testChar - The character to test
+    *
+    * Return true if the char is a delimiter, false otherwise
     */
-    pub fn set_datas(&mut self, datas : ~[char]) -> () {
-        self.datas = datas;
-        self.pos = 0;
+    fn is_delimiter(&self, testChar : char) -> bool {
+        self.charTable.has(testChar, DELIMITER)
     }
 
     /**
-    * Update the vector of data to tokeny by a new string.
+    * Private function, test if a char is a special char.
+    *
+    * # Arguments
+    * * testChar - The character to test
+    *
+    * Return true if the char is a special char, false otherwise
     */
-    pub fn set_datas_with_str(&mut self, datas : ~str) -> () {
-        self.datas = str_to_vec(datas)
+    fn is_special_char(&self, testChar : char) -> bool {
+        self.charTable.has(testChar, SPECIAL_CHAR)
     }
-    
+
     /**
-    * Add a new key word to the StringTokenizer dictionnary.
+    * Private function, test if a char open a string.
     *
     * # Arguments
-    * * keyword - A string who contains the new keyword to add
+    * * testChar - The character to test
+    *
+    * Return true if the char is a quote char, false otherwise
     */
-    pub fn add_keyword(&mut self, keyword : ~str) -> () {
-        self.keyWords.push(keyword)
+    fn is_quote_char(&self, testChar : char) -> bool {
+        self.charTable.has(testChar, QUOTE_CHAR)
     }
 
     /**
-    * Add a new delimiter to the StringTokenizer dictionnary.
+    * Private function, compile the dictionnaries into the lookup tables
+    * used to find the tokens, nothing is done if the tables are up to date.
+    *
+    * The delimiters, special chars and quote chars are stored in a CharTable
+    * indexed by char. The comments markers, the operators, the keywords and
+    * the custom rules are compiled into one deterministic automaton, the
+    * Lexicon. It is run once from the begin of each token and its matches
+    * give the comment, the longest operator, the keyword ending with the word
+    * and the longest custom rule without reading the chars again.
+    */
+    fn compile(&mut self) -> () {
+        let mut charTable = CharTable::new();
+        let mut lexicon = Lexicon::new();
+
+        if self.compiled {
+            return;
+        }
+        for self.delimiters.iter().advance |delim| {
+            charTable.add(*delim, DELIMITER);
+        }
+        for self.specialChars.iter().advance |spec| {
+            charTable.add(*spec, SPECIAL_CHAR);
+        }
+        for self.quoteChars.iter().advance |quote| {
+            charTable.add(*quote, QUOTE_CHAR);
+        }
+        match self.comments {
+            CplusplusComments   => lexicon.add_literal("//", false, CommentMarker(CPP_COMMENT_TAG)),
+            CComments           => lexicon.add_literal("/*", false, CommentMarker(C_COMMENT_TAG)),
+            AllComments         => {
+                lexicon.add_literal("/*", false, CommentMarker(C_COMMENT_TAG));
+                lexicon.add_literal("//", false, CommentMarker(CPP_COMMENT_TAG));
+            }
+            NoComments          => {}
+        }
+        if self.multiCommentEnd.len() > 0 {
+            lexicon.add_literal(self.multiCommentBegin, false, CommentMarker(MULTI_COMMENT_TAG));
+        }
+        lexicon.add_literal(self.singleComment, false, CommentMarker(SINGLE_COMMENT_TAG));
+        for self.operators.iter().enumerate().advance |(i, operator)| {
+            lexicon.add_literal(*operator, false, FixedOperator(i));
+        }
+        for self.keyWords.iter().enumerate().advance |(i, keyword)| {
+            lexicon.add_literal(*keyword, self.caseInsensitiveKeywords, FixedKeyword(i));
+        }
+        for self.rules.iter().enumerate().advance |(i, rule)| {
+            lexicon.add_regex(rule.regex.clone(), CustomPattern(i));
+        }
+        lexicon.build();
+        self.charTable = charTable;
+        self.lexicon = lexicon;
+        self.compiled = true;
+    }
+
+    /**
+    * Private function, run the Lexicon from a position, its matches are kept
+    * until an other position is scanned.
+    */
+    fn scan(&mut self, pos : uint) -> () {
+        self.compile();
+        self.lexicon.scan(self.datas, pos);
+    }
+
+    /**
+    * Private function, test if a string is present in the datas at a given position.
+    *
+    * # Arguments
+    * * pos - The position where the string must begin
+    * * pattern - The string to search
+    *
+    * Return true if the datas contains the string at pos, false otherwise
+    */
+    fn matches_at(&self, pos : uint, pattern : &str) -> bool {
+        let mut i = pos;
+
+        if pattern.len() == 0 {
+            return false;
+        }
+        for pattern.iter().advance |pchar| {
+            if i >= self.datas.len() || self.datas[i] != pchar {
+                return false;
+            }
+            i += 1;
+        }
+        return true
+    }
+
+    /**
+    * Update the vector of data to tokenize by a new one.
+    */
+    pub fn set_datas(&mut self, datas : ~[char]) -> () {
+        self.reset_modes();
+        self.interpolations.clear();
+        self.reset_indentation();
+        self.history.clear();
+        self.lastToken = NoToken;
+        self.lastTokenEnd = 0;
+        self.source = datas;
+        self.pos = 0;
+        self.triviaEnd = 0;
+        self.endOfDatas = false;
+        self.join_lines();
+    }
+
+    /**
+    * Private function, build the datas to tokenize by removing the line
+    * continuations from the source.
+    *
+    * The current position is kept on the same char of the source.
+    */
+    fn join_lines(&mut self) -> () {
+        let current = self.physical_pos(self.pos);
+        let mut datas = ~[];
+        let mut physical = ~[];
+        let mut i = 0;
+
+        while i < self.source.len() {
+            let mut joined = 0;
+            match self.lineContinuation {
+                Some(ref continuation) if self.source_matches_at(i, *continuation) => {
+                    let breakPos = i + continuation.char_len();
+                    if breakPos < self.source.len() && self.is_line_break(self.source[breakPos]) {
+                        joined = continuation.char_len() + 1;
+                        if self.source[breakPos] == '\r' && breakPos + 1 < self.source.len()
+                            && self.source[breakPos + 1] == '\n' {
+                            joined += 1;
+                        }
+                    }
+                }
+                _                                                                  => {}
+            }
+            if joined > 0 {
+                i += joined;
+            }
+            else {
+                datas.push(self.source[i]);
+                physical.push(i);
+                i += 1;
+            }
+        }
+        self.pos = 0;
+        while self.pos < physical.len() && physical[self.pos] < current {
+            self.pos += 1;
+        }
+        self.datas = datas;
+        self.lexicon.forget();
+        self.physical = if self.lineContinuation.is_some() { physical } else { ~[] };
+    }
+
+    /**
+    * Private function, test if a string is in the source at a position.
+    */
+    fn source_matches_at(&self, pos : uint, pattern : &str) -> bool {
+        let mut i = pos;
+
+        if pattern.len() == 0 {
+            return false;
+        }
+        for pattern.iter().advance |pchar| {
+            if i >= self.source.len() || self.source[i] != pchar {
+                return false;
+            }
+            i += 1;
+        }
+        return true
+    }
+
+    /**
+    * Private function, find the position in the source of a position of the datas.
+    *
+    * Return the position in the source
+    */
+    fn physical_pos(&self, pos : uint) -> uint {
+        if self.physical.len() == 0 {
+            pos
+        }
+        else if pos < self.physical.len() {
+            self.physical[pos]
+        }
+        else {
+            self.source.len()
+        }
+    }
+
+    /**
+    * Define if the tokenization is lossless.
+    *
+    * In lossless mode each token carry the delimiters and the comments around it :
+    * the leading trivia are all the chars between the previous token and the token,
+    * the trailing trivia are the delimiters and the comments following the token
+    * until the end of its line. An EndOfFile token carry the trivia after the last
+    * token. The concatenation of the full texts of the tokens is the datas,
+    * see get_full_text.
+    *
+    * # Default
+    * * By default the tokenization is not lossless
+    *
+    * # Arguments
+    * * lossless - true for a lossless tokenization, false otherwise
+    */
+    pub fn set_lossless(&mut self, lossless : bool) -> () {
+        self.lossless = lossless;
+    }
+
+    /**
+    * Define the sequence who join a line with the next one when it is just
+    * before a line break.
+    *
+    * The sequence and the line break are removed from the datas, the spans of
+    * the tokens stay the positions in the original datas.
+    *
+    * # Default
+    * * By default there is no line continuation
+    *
+    * # Example
+    * * With Some(~"\\") the string |ec\\\nho| provide the token |echo|
+    *
+    * # Arguments
+    * * continuation - The sequence who continue a line, None to keep the lines
+    */
+    pub fn set_line_continuation(&mut self, continuation : Option<~str>) -> () {
+        self.lineContinuation = continuation;
+        self.join_lines();
+    }
+
+    /**
+    * Update the vector of data to tokeny by a new string.
+    */
+    pub fn set_datas_with_str(&mut self, datas : ~str) -> () {
+        self.set_datas(str_to_vec(datas))
+    }
+    
+    /**
+    * Add a new key word to the StringTokenizer dictionnary.
+    *
+    * # Arguments
+    * * keyword - A string who contains the new keyword to add
+    */
+    pub fn add_keyword(&mut self, keyword : ~str) -> () {
+        let id = self.keyWords.len();
+        self.add_keyword_with_id(keyword, id, OtherKeyword);
+    }
+
+    /**
+    * Add a new soft key word to the StringTokenizer dictionnary.
+    *
+    * A soft keyword is a keyword only at some positions. It provide a Word token
+    * with the maybeKeyword attribute set to true, the keyword attributes of the
+    * token are also set. If a predicate is given with set_soft_keyword_predicate
+    * it decide if the token is a KeyWord or a Word.
+    *
+    * # Arguments
+    * * keyword - A string who contains the new soft keyword to add
+    */
+    pub fn add_soft_keyword(&mut self, keyword : ~str) -> () {
+        let id = self.keyWords.len();
+        self.add_keyword_with_id(keyword, id, ContextualKeyword);
+    }
+
+    /**
+    * Set the predicate who decide if a soft keyword is a keyword.
+    *
+    * The predicate is called with the soft keyword and the previous tokens, the
+    * last one is the token just before the soft keyword. At most four tokens
+    * are given.
+    *
+    * # Arguments
+    * * predicate - Return true if the soft keyword is a keyword, None to provide
+    *   the soft keywords as words
+    */
+    pub fn set_soft_keyword_predicate(&mut self,
+                                      predicate : Option<@fn(&str, &[TokenData]) -> bool>) -> () {
+        self.softKeywordPredicate = predicate;
+        self.history.clear();
+    }
+
+    /**
+    * Add a new boolean literal to the StringTokenizer dictionnary.
+    *
+    * The literal provide a Bool token, the literals are found before the keywords
+    * and are compared like the keywords ( see set_case_insensitive_keywords ).
+    *
+    * # Arguments
+    * * literal - The spelling of the literal ( true / True / yes )
+    * * value - The value of the literal
+    */
+    pub fn add_bool_literal(&mut self, literal : ~str, value : bool) -> () {
+        self.literals.push((literal, Some(value)));
+    }
+
+    /**
+    * Add a new null literal to the StringTokenizer dictionnary.
+    *
+    * The literal provide a Null token, the literals are found before the keywords
+    * and are compared like the keywords ( see set_case_insensitive_keywords ).
+    *
+    * # Arguments
+    * * literal - The spelling of the literal ( null / nil / None )
+    */
+    pub fn add_null_literal(&mut self, literal : ~str) -> () {
+        self.literals.push((literal, None));
+    }
+
+    /**
+    * Add a new key word with an id and a category to the StringTokenizer dictionnary.
+    *
+    * The id and the category are given by the keywordId and keywordCategory
+    * attributes of the KeyWord tokens. The keywords added with add_keyword have
+    * their position in the dictionnary as id and the OtherKeyword category.
+    *
+    * # Arguments
+    * * keyword - A string who contains the new keyword to add
+    * * id - The id of the keyword
+    * * category - The category of the keyword
+    */
+    pub fn add_keyword_with_id(&mut self, keyword : ~str, id : uint,
+                               category : KeywordCategory) -> () {
+        self.compiled = false;
+        self.keyWords.push(keyword);
+        self.keywordKinds.push((id, category));
+    }
+
+    /**
+    * Define if the keywords are found without regard to the case.
+    *
+    * The words and the keywords are compared after the Unicode simple case
+    * folding, so a char who only have a full case folding ( ß ) don't match
+    * its expansion ( ss ). The keyword attribute of a
+    * KeyWord token contain the spelling used in add_keyword, the word attribute
+    * contain the spelling of the datas.
+    *
+    * # Default
+    * * By default the keywords are case sensitive
+    *
+    * # Arguments
+    * * insensitive - true if the case of the keywords is ignored, false otherwise
+    */
+    pub fn set_case_insensitive_keywords(&mut self, insensitive : bool) -> () {
+        self.compiled = false;
+        self.caseInsensitiveKeywords = insensitive;
+    }
+
+    /**
+    * Add a new delimiter to the StringTokenizer dictionnary.
     *
     * # Default
     * * '\t' 
@@ -296,7 +1399,8 @@ impl StringTokenizer {
     * * delimiter - The char containing the delimiter to add
     */
     pub fn add_delimiter(&mut self, delimiter : char) -> () {
-        self.delimiters.push(delimiter)
+        self.compiled = false;
+        self.delimiters.push(delimiter);
     }
     
     /**
@@ -312,428 +1416,5252 @@ impl StringTokenizer {
     * * specialchar - The new char to add to the special chars list
     */
     pub fn add_specialchar(&mut self, specialchar : char) -> () {
-        self.specialChars.push(specialchar)
+        self.compiled = false;
+        self.specialChars.push(specialchar);
     }
 
     /**
-    * Set the type of comments handled by the StringTokenizer.
+    * Add a new operator to the StringTokenizer dictionnary.
+    *
+    * An operator is a sequence of chars returned as one Operator token,
+    * the longest operator matching the datas is always choosen.
     *
     * # Default
-    * * No comments are handled by default 
+    * * No operators are handled by default
     *
     * # Arguments
-    * * comments - The new type of comments to handle
+    * * operator - The string containing the operator to add
     */
-    pub fn set_comments(&mut self, comments : CommentsTypes) -> () {
-        self.comments = comments
+    pub fn add_operator(&mut self, operator : ~str) -> () {
+        self.compiled = false;
+        self.operators.push(operator);
     }
 
     /**
-    * Define if the '\n' char is a new token or a delimiter
+    * Add a new custom rule to the StringTokenizer dictionnary.
+    *
+    * The rules are tried at the begin of each token, the longest match wins
+    * and the priority choose between two matches of the same length.
+    * A rule wins against the built-in tokens ( word, number, special char... )
+    * if it match at least as many chars, a negative priority let the built-in
+    * token win when they have the same length.
+    *
+    * # Example
+    * * st.add_rule(~"color", "#[0-9a-f]{6}", 0) provide Custom tokens for "#ff00aa"
     *
-    * # Default
-    * * By default '\n' is a token
-    * 
     * # Arguments
-    * isToken - true if it's a token false otherwise
+    * * name - The name of the rule, returned in customKind
+    * * regex - The regular expression of the rule ( see the regex module )
+    * * priority - The priority of the rule
+    *
+    * Return an error message if the regular expression is not valid.
     */
-    pub fn set_new_line_as_token(&mut self, isToken : bool) -> () {
-        self.returnIsToken = isToken;
-        if isToken == true {
-            self.add_specialchar('\n');
-        }
-        else {
-            self.add_delimiter('\n');
+    pub fn add_rule(&mut self, name : ~str, regex : &str, priority : int) -> Result<(), ~str> {
+        match Regex::new(regex) {
+            Ok(compiled)    => {
+                self.compiled = false;
+                self.rules.push(CustomRule {
+                    name : name,
+                    pattern : regex.to_owned(),
+                    regex : compiled,
+                    priority : priority
+                });
+                Ok(())
+            }
+            Err(error)      => Err(error)
         }
-            
     }
-    
+
     /**
-    * Ignore or not the escape char.
+    * Add a new interpolated string literal to the StringTokenizer dictionnary.
     *
-    * # Default
-    * * By default escape char is not ignored
+    * An interpolated string provide these tokens : StrStart ( prefix and quote ),
+    * StrFragment ( raw text ), InterpStart, the tokens of the expression,
+    * InterpEnd and StrEnd ( quote ). The brackets opened in the expression and
+    * the strings nested in it are handled.
     *
     * # Example
-    * * ignored - This string |"This is a message :\"Hello World\""| provide these tokens |"This is a message : \" - Hello - World\ - ""|
-    * * not ignored - This string |"This is a message :\"Hello World\""| provide this tokens |"This is a message :\"Hello World\""|
-    * 
+    * * st.add_interpolated_string(~"", '"', ~"${", ~"}") for "Hello ${name}!"
+    * * st.add_interpolated_string(~"f", '"', ~"{", ~"}") for f"{x+1}"
+    *
     * # Arguments
-    * * ignore - Ture if escape char must be ignored, false otherwise
+    * * prefix - The string before the quote, can be empty
+    * * quote - The char who begin and end the string
+    * * begin - The string who begin an interpolation
+    * * end - The string who end an interpolation
     */
-    pub fn ignore_escape_char(&mut self, ignore : bool) -> () {
-        self.ignoreEscapeChar = ignore
+    pub fn add_interpolated_string(&mut self, prefix : ~str, quote : char,
+                                   begin : ~str, end : ~str) -> () {
+        let open = match end.as_slice() {
+            "}"     => ~"{",
+            ")"     => ~"(",
+            "]"     => ~"[",
+            _       => ~""
+        };
+        self.interpolatedStrings.push(InterpolatedString {
+            prefix : prefix,
+            quote : quote,
+            begin : begin,
+            end : end,
+            open : open
+        });
     }
 
     /**
-    * Reset all the settings contained on the StringTokenizer
-    * ( datas / keywords / delimiters / specialchars / tokens ).
+    * Set the chars who begin and end a string.
+    *
+    * # Default
+    * * '"' '\''
+    *
+    * # Arguments
+    * * quotes - The new list of quote chars
     */
-    pub fn reset_settings(&mut self) -> () {
-        self.delimiters.clear();
-        self.keyWords.clear();
-        self.datas.clear();
-        self.pos = 0;
-        self.specialChars.clear();
-        self.comments = NoComments;
-        self.returnIsToken = true;
-        self.token = NoToken;
-        self.number = ~"";
-        self.word = ~"";
-        self.keyword = ~"";
-        self.multiCommentBegin = ~"";
-        self.multiCommentEnd = ~"";
-        self.singleComment = ~"";
-        self.specialChar = 0 as char;
+    pub fn set_quote_chars(&mut self, quotes : ~[char]) -> () {
+        self.compiled = false;
+        self.quoteChars = quotes;
     }
 
     /**
-    * Set a custom multi line comment
+    * Define if hexadecimal numbers (0x1F) are recognized.
+    *
+    * # Default
+    * * By default hexadecimal numbers are not recognized
     *
     * # Arguments
-    * * commentBegin - The string who represent the begin of the comment.
-    * * commentEnd - The string who represent the end of the comment.
+    * * allow - true if hexadecimal numbers are numbers, false otherwise
     */
-    pub fn set_multi_line_custom_comment(&mut self, commentBegin : ~str, commentEnd : ~str) -> () {
-        self.multiCommentBegin = commentBegin;
-        self.multiCommentEnd = commentEnd;
+    pub fn allow_hex_numbers(&mut self, allow : bool) -> () {
+        self.hexNumbers = allow
     }
-    
+
     /**
-    * Set a custom single line comment.
+    * Define if numbers with an exponent (1.5e-10) are recognized.
+    *
+    * # Default
+    * * By default exponents are not recognized
     *
     * # Arguments
-    * * commentBegin - The str who represent the begin of a custom comment
+    * * allow - true if numbers can have an exponent, false otherwise
     */
-    pub fn set_single_line_custom_comment(&mut self, commentBegin : ~str) -> () {
-        self.singleComment = commentBegin;
+    pub fn allow_exponent_numbers(&mut self, allow : bool) -> () {
+        self.exponentNumbers = allow
     }
-    
+
     /**
-    * Private function, retrieve a string in double cote.
+    * Define if signed numbers (-42 / +1.5 / -Infinity / NaN) are recognized.
+    *
+    * A '+' or '-' char is the sign of a number if the previous token is an operator,
+    * a special char who is not a closing bracket, or is on a previous line.
+    * Otherwise the sign stay a special char ( a - 1 ). When the sign is also an
+    * identifier char ( see add_identifier_char ) the binary operators must be
+    * surrounded by delimiters, so a sign after a delimiter always begin a number
+    * ( (foo -1) ). The Infinity and NaN words are also numbers.
+    *
+    * # Default
+    * * By default the signs are special chars
     *
-    * Return the string contained between two simple cotes.
+    * # Arguments
+    * * allow - true if numbers can have a sign, false otherwise
     */
-    fn get_double_cote_string(&mut self) -> ~str {
-        let mut tstr : ~[char] = ~[];
-        let mut end = false;
-        let mut cntS = 0;
-        let mut nxtD = false;
-
-        tstr.push(self.datas[self.pos]);
-        self.pos += 1;
-        while self.pos < self.datas.len() 
-            && !end {
-            if nxtD == true {
-                nxtD = false;
-                cntS = 0;
-            }
-            if self.datas[self.pos] == '\\' {
-                cntS += 1;
-                if cntS == 3 {
-                    cntS = 1;
-                }
-            }
-            else if cntS > 0{
-                nxtD = true;
-            }
-            tstr.push(self.datas[self.pos]);
-            if self.datas[self.pos] == '"'
-                && (cntS == 2 || cntS == 0) {
-                end = true;
-            }
-            self.pos += 1;
-        } 
-        str::from_chars(tstr)
+    pub fn allow_signed_numbers(&mut self, allow : bool) -> () {
+        self.signedNumbers = allow
     }
 
     /**
-    * Get a String in simple cote.
+    * Define if the semantic versions are recognized ( 1.2.3 / 1.0.0-rc.1+build.5 ).
+    *
+    * The major, minor and patch numbers can't begin by a 0 ( 2024.01.18 is not a
+    * version ), a Version token is provided.
+    *
+    * # Default
+    * * By default the versions are not recognized
     *
-    * Return all the string contained between two simple cotes.
+    * # Arguments
+    * * allow - true if the versions are recognized, false otherwise
     */
-    fn get_simple_cote_string(&mut self) -> ~str {
-        let mut tstr : ~[char] = ~[];
-        let mut end = false;
-        let mut cntS = 0;
-        let mut nxtD = false;
-        
-        tstr.push(self.datas[self.pos]);
-        self.pos += 1;
-        while self.pos < self.datas.len() 
-            && !end {
-            if nxtD == true {
-                nxtD = false;
-                cntS = 0;
-            }
-            if self.datas[self.pos] == '\\' {
-                cntS += 1;
-                if cntS == 3 {
-                    cntS = 1;
-                }
-            }
-            else if cntS > 0{
-                nxtD = true;
-            }
-            tstr.push(self.datas[self.pos]);
-            if self.datas[self.pos] == '\''
-                && (cntS == 2 || cntS == 0) {
-                end = true;
-            }
-            self.pos += 1;
-        } 
-        str::from_chars(tstr)
+    pub fn allow_versions(&mut self, allow : bool) -> () {
+        self.versions = allow
     }
 
     /**
-    * Private function, get the next from the current position.
+    * Define if the IPv4 and IPv6 addresses are recognized ( 192.168.0.1 / fe80::0:1 ).
     *
-    * Return the word read.
+    * An IPv6 address must contain at least one decimal digit, the words made of
+    * hexadecimal letters ( add::bad ) are kept. It must follow a delimiter or a
+    * special char other than ':' and '.', and be written with 8 groups, or with
+    * a '::' and at least 3 ':' ( 2001:db8::1 ), or begin by '::' ( ::1 ), so the
+    * paths of the languages ( f32::abs / cafe::1 ) are kept. An IpAddress token
+    * is provided.
+    *
+    * # Default
+    * * By default the addresses are not recognized
+    *
+    * # Arguments
+    * * allow - true if the addresses are recognized, false otherwise
     */
-    fn get_word(&mut self) -> ~str {
-        let mut tstr : ~[char] = ~[];
-        
-        if self.datas[self.pos] == '"' {
-            return self.get_double_cote_string();
-        }
-        else if self.datas[self.pos] == '\'' {
-            return self.get_simple_cote_string();
-        } 
-        while self.pos < self.datas.len()
-            && !self.is_delimiter(self.datas[self.pos])
-            && !self.is_special_char(self.datas[self.pos]){
-            tstr.push(self.datas[self.pos]);
-            self.pos += 1;
-        }
-        str::from_chars(tstr)
+    pub fn allow_ip_addresses(&mut self, allow : bool) -> () {
+        self.ipAddresses = allow
     }
 
     /**
-    * Private function, handle C++ style comment
+    * Define if the numbers with more than one dot are recognized ( 2024.10.18 ).
     *
-    * Return true if a comment is found, false otherwise
+    * The versions and the addresses are found first if they are allowed,
+    * a DottedNumber token is provided.
+    *
+    * # Default
+    * * By default the dotted numbers are not recognized
+    *
+    * # Arguments
+    * * allow - true if the dotted numbers are recognized, false otherwise
     */
-    fn c_plus_plus_comments(&mut self) -> bool {
-        let mut end = false;
-        if self.pos <= self.datas.len() - 2 {
-            if self.datas[self.pos] == '/'
-                && self.datas[self.pos + 1] == '/' {
-                while self.pos < self.datas.len() 
-                    && !end {
-                    if self.datas[self.pos] == '\n' {
-                        end = true
-                    }
-                    self.pos += 1;
-                }
-                return true;
-            }
-                    else {
-                        return false;
-                    }
-                    
-                }
-        else {
-            return false;
-        }
+    pub fn allow_dotted_numbers(&mut self, allow : bool) -> () {
+        self.dottedNumbers = allow
     }
-    
+
     /**
-    * Private function, handle C style comment erasing
+    * Define if the dates, times and durations are recognized.
     *
-    * Return true if a C style comment is found, false otherwise
+    * The recognized forms are the ISO 8601 dates and timestamps ( 2024-10-18 /
+    * 2024-10-18T12:30:00.5+02:00 ), the HH:MM:SS times and the durations made of
+    * numbers followed by d, h, m, s, ms, us or ns ( 1h30m / 1.5s ). The tokens are
+    * Date, Time, DateTime and Duration, the timestamp attribute contain the components
+    * of the dates and times and the duration attribute the nanoseconds of the durations.
+    *
+    * The recognizer is only built when the library is compiled with
+    * `--cfg date_times`, without it the switch is kept in the settings but the
+    * dates, times and durations are never recognized.
+    *
+    * # Default
+    * * By default the dates, times and durations are not recognized
+    *
+    * # Arguments
+    * * allow - true if the dates, times and durations are recognized, false otherwise
     */
-    fn c_comments(&mut self) -> bool {
-        let mut end = false;
-        if self.pos <= self.datas.len() - 2 {
-            if self.datas[self.pos] == '/'
-                && self.datas[self.pos + 1] == '*' {
-                self.pos += 2;
-                while self.pos < self.datas.len()
-                    && !end {
-                    if self.pos + 1 <= self.datas.len()
-                        && (self.datas[self.pos] == '*' && self.datas[self.pos + 1] == '/') {
-                        end = true;
-                        if (self.pos < self.datas.len()) {
-                            self.pos += 1;
-                        }
-                    }
-                    self.pos += 1;
-                }
-                return true;
-            }
-            
-            else {
-                return false;
-            }
+    pub fn allow_date_times(&mut self, allow : bool) -> () {
+        self.dateTimes = allow
+    }
+
+    /**
+    * Define if the URLs are recognized ( http://example.com/a/b?c=d#e ).
+    *
+    * An URL is a scheme followed by "://", the punctuation at the end of the URL
+    * ( http://example.com. ) is not kept. An Url token is provided.
+    *
+    * # Default
+    * * By default the URLs are not recognized
+    *
+    * # Arguments
+    * * allow - true if the URLs are recognized, false otherwise
+    */
+    pub fn allow_urls(&mut self, allow : bool) -> () {
+        self.urls = allow
+    }
+
+    /**
+    * Define if the email addresses are recognized ( jane.doe+tag@example.com ).
+    *
+    * The domain must contain at least one dot. An Email token is provided.
+    *
+    * # Default
+    * * By default the email addresses are not recognized
+    *
+    * # Arguments
+    * * allow - true if the email addresses are recognized, false otherwise
+    */
+    pub fn allow_emails(&mut self, allow : bool) -> () {
+        self.emails = allow
+    }
+
+    /**
+    * Define if the filesystem paths are recognized.
+    *
+    * The POSIX paths begin by '/', "./", "../" or "~/" ( /usr/bin / ./run.sh ),
+    * the Windows paths by a drive or two backslashes ( C:\Users / \\server\share ). A path
+    * can't follow a letter, a digit or a closing bracket, "a/b" stay a division.
+    * When '/' is a special char, a path beginning by '/' must be followed by a
+    * letter, a digit or '_' and can't follow an operand, "a /b" stay a division
+    * but "x = /usr/bin" is a path. A Path token is provided.
+    *
+    * # Default
+    * * By default the paths are not recognized
+    *
+    * # Arguments
+    * * allow - true if the paths are recognized, false otherwise
+    */
+    pub fn allow_paths(&mut self, allow : bool) -> () {
+        self.paths = allow
+    }
+
+    /**
+    * Set the char allowed between the digits of a number (1_000_000).
+    *
+    * # Default
+    * * None
+    *
+    * # Arguments
+    * * separator - The digit separator, None to disable it
+    */
+    pub fn set_digit_separator(&mut self, separator : Option<char>) -> () {
+        self.digitSeparator = separator
+    }
+
+    /**
+    * Add a new unit to the StringTokenizer dictionnary.
+    *
+    * A number directly followed by a unit provide a Dimension token ( 10px / 1.5em /
+    * 50% / 2GiB ), the number attribute contain the value and the unit attribute
+    * the unit. The longest unit is used.
+    *
+    * # Arguments
+    * * unit - The spelling of the unit
+    */
+    pub fn add_unit(&mut self, unit : ~str) -> () {
+        self.units.push(unit);
+    }
+
+    /**
+    * Add a new sigil to the StringTokenizer dictionnary.
+    *
+    * A sigil directly followed by an identifier provide a Sigiled token ( $var /
+    * @attr / #tag / %macro ), the sigil attribute contain the sigil and the name
+    * attribute the identifier. The identifier is made of letters, digits and '_'
+    * and can't begin by a digit ( see add_numbered_sigil ).
+    * A sigil who is not followed by an identifier is a special char or a part
+    * of a word as usual.
+    *
+    * # Arguments
+    * * sigil - The sigil char
+    */
+    pub fn add_sigil(&mut self, sigil : char) -> () {
+        self.sigils.push(sigil);
+    }
+
+    /**
+    * Add a new sigil who can also be followed by a number ( $1 / $0 ).
+    *
+    * The sigil work like add_sigil, in addition the digit directly after the sigil
+    * provide a Sigiled token with the digit as name. Like the positional
+    * parameters of the shells, only one digit is read ( $10 is $1 followed by 0 ).
+    *
+    * # Arguments
+    * * sigil - The sigil char
+    */
+    pub fn add_numbered_sigil(&mut self, sigil : char) -> () {
+        self.sigils.push(sigil);
+        self.numberedSigils.push(sigil);
+    }
+
+    /**
+    * Add a special char who can be used inside the identifiers.
+    *
+    * The char is part of a word if it follow a word who don't begin by a digit
+    * ( foo-bar / nil? / set! ), or if it begin a word followed by a letter
+    * ( --custom-prop / *out* ). Elsewhere it stay a special char ( a - 1 / - 1 ).
+    *
+    * # Arguments
+    * * identifierChar - The special char allowed in the identifiers
+    */
+    pub fn add_identifier_char(&mut self, identifierChar : char) -> () {
+        self.identifierChars.push(identifierChar);
+    }
+
+    /**
+    * Load a language profile.
+    *
+    * Replace the keywords, delimiters, special chars, operators, comments,
+    * strings, numbers and custom rules by the one of the profile. The datas
+    * are kept.
+    *
+    * # Arguments
+    * * profile - The language profile to load
+    */
+    pub fn set_profile(&mut self, profile : Profile) -> () {
+        self.compiled = false;
+        self.keyWords.clear();
+        self.keywordKinds.clear();
+        self.literals.clear();
+        self.caseInsensitiveKeywords = false;
+        self.delimiters.clear();
+        self.specialChars.clear();
+        self.operators.clear();
+        self.comments = NoComments;
+        self.multiCommentBegin = ~"";
+        self.multiCommentEnd = ~"";
+        self.singleComment = ~"";
+        self.interpolatedStrings.clear();
+        self.offsideRule = false;
+        self.unicodeNewLines = false;
+        self.collapseBlankLines = false;
+        self.lineContinuation = None;
+        self.join_lines();
+        self.quoteChars = ~['"', '\''];
+        self.ignoreEscapeChar = false;
+        self.stringPrefixes.clear();
+        self.tripleQuotes = false;
+        self.doubledQuotes = false;
+        self.nestedComments = false;
+        self.rules.clear();
+        self.hexNumbers = false;
+        self.exponentNumbers = false;
+        self.signedNumbers = false;
+        self.versions = false;
+        self.ipAddresses = false;
+        self.dottedNumbers = false;
+        self.dateTimes = false;
+        self.urls = false;
+        self.emails = false;
+        self.paths = false;
+        self.digitSeparator = None;
+        self.units.clear();
+        self.sigils.clear();
+        self.numberedSigils.clear();
+        self.identifierChars.clear();
+        self.add_delimiters([' ', '\t', '\r']);
+        match profile {
+            CProfile            => self.load_c_profile(),
+            RustProfile         => self.load_rust_profile(),
+            PythonProfile       => self.load_python_profile(),
+            JavaScriptProfile   => self.load_javascript_profile(),
+            SqlProfile          => self.load_sql_profile(),
+            ShellProfile        => self.load_shell_profile(),
+            JsonProfile         => self.load_json_profile(),
+            CssProfile          => self.load_css_profile(),
+            LispProfile         => self.load_lisp_profile()
         }
-        else {
-            return false;
+    }
+
+    /**
+    * Private function, add a list of keywords.
+    */
+    fn add_keywords(&mut self, keywords : &[&str]) -> () {
+        for keywords.iter().advance |keyword| {
+            self.add_keyword(keyword.to_owned());
         }
-        
     }
 
     /**
-    * Private function, Check if there is comments and delete them
-    *
-    * Return true if a comment is found.
+    * Private function, add the boolean and null literals.
     */
-    fn has_comments(&mut self) -> bool {
-        if self.pos == self.datas.len() {
-            return false;
+    fn add_literals(&mut self, trueLiteral : &str, falseLiteral : &str, nullLiteral : &str) -> () {
+        self.add_bool_literal(trueLiteral.to_owned(), true);
+        self.add_bool_literal(falseLiteral.to_owned(), false);
+        if nullLiteral.len() > 0 {
+            self.add_null_literal(nullLiteral.to_owned());
         }
-        else {
-            if self.c_comments() {
-                return true;
-            }
-            else if self.c_plus_plus_comments() {
-                return true;
-            }
-            else {
-                return false;
-            }
+    }
+
+    /**
+    * Private function, add a list of units.
+    */
+    fn add_units(&mut self, units : &[&str]) -> () {
+        for units.iter().advance |unit| {
+            self.add_unit(unit.to_owned());
         }
     }
-    
+
     /**
-    * Private function, consume all delimiters or comments
+    * Private function, add a list of identifier chars.
     */
-    fn clean_for_next_token(&mut self) -> bool {
-        self.consume_delimiters();
-        while self.has_comments() {
-            self.consume_delimiters();
+    fn add_identifier_chars(&mut self, identifierChars : &[char]) -> () {
+        for identifierChars.iter().advance |identifierChar| {
+            self.add_identifier_char(*identifierChar);
         }
-        if self.pos == self.datas.len() {
-            false
+    }
+
+    /**
+    * Private function, add a list of soft keywords.
+    */
+    fn add_soft_keywords(&mut self, keywords : &[&str]) -> () {
+        for keywords.iter().advance |keyword| {
+            self.add_soft_keyword(keyword.to_owned());
         }
-        else {
-            true
+    }
+
+    /**
+    * Private function, add a list of operators.
+    */
+    fn add_operators(&mut self, operators : &[&str]) -> () {
+        self.compiled = false;
+        for operators.iter().advance |operator| {
+            self.operators.push(operator.to_owned());
         }
     }
 
     /**
-    * Private function, check if the found word is a number
-    *
-    * Return true if it's a number, false otherwise
+    * Private function, add a list of special chars.
     */
-    fn is_number(&mut self) -> bool {
-        let mut dot = 0;
- 
-        for self.word.iter().advance |schar| {
-            if schar == '.' {
-                dot += 1;
-            }
-            if !schar.is_digit() {
-                return false;
-            }
+    fn add_specialchars(&mut self, specialchars : &[char]) -> () {
+        self.compiled = false;
+        for specialchars.iter().advance |spec| {
+            self.specialChars.push(*spec);
         }
-        if dot > 1 {
-            return false;
+    }
+
+    /**
+    * Private function, add a list of delimiters.
+    */
+    fn add_delimiters(&mut self, delimiters : &[char]) -> () {
+        self.compiled = false;
+        for delimiters.iter().advance |delim| {
+            self.delimiters.push(*delim);
         }
-        return true;
     }
 
+    /**
+    * Private function, load the C language profile.
+    */
+    fn load_c_profile(&mut self) -> () {
+        self.add_keywords(["auto", "break", "case", "char", "const", "continue",
+                           "default", "do", "double", "else", "enum", "extern",
+                           "float", "for", "goto", "if", "inline", "int", "long",
+                           "register", "restrict", "return", "short", "signed",
+                           "sizeof", "static", "struct", "switch", "typedef",
+                           "union", "unsigned", "void", "volatile", "while"]);
+        self.add_operators(["<<=", ">>=", "...", "->", "++", "--", "<<", ">>",
+                            "<=", ">=", "==", "!=", "&&", "||", "+=", "-=",
+                            "*=", "/=", "%=", "&=", "^=", "|="]);
+        self.add_specialchars(['{', '}', '[', ']', '(', ')', '=', '!', '<', '>',
+                               '&', '^', '|', '+', '-', '/', '%', '*', ';', '?',
+                               ':', ',', '.', '~', '#']);
+        self.set_new_line_as_token(false);
+        self.set_line_continuation(Some(~"\\"));
+        self.comments = AllComments;
+        self.hexNumbers = true;
+        self.exponentNumbers = true;
+    }
 
     /**
-    * Private functions, check if the found word is a keyword
+    * Private function, load the Rust language profile.
     *
-    * Return true if it is a keyword, false otherwise
+    * The char literals and the lifetimes are Custom tokens ( char / lifetime ),
+    * the block comments can be nested.
     */
-    fn is_keyword(&self) -> bool {
-        for self.keyWords.iter().advance |keyword| {
-            if keyword == &self.word {
-                return true;
-            }
-        }
-        return false
+    fn load_rust_profile(&mut self) -> () {
+        self.add_keywords(["as", "async", "await", "break", "const", "continue",
+                           "crate", "dyn", "else", "enum", "extern", "fn",
+                           "for", "if", "impl", "in", "let", "loop", "match", "mod",
+                           "move", "mut", "pub", "ref", "return", "self", "Self",
+                           "static", "struct", "super", "trait", "type",
+                           "unsafe", "use", "where", "while"]);
+        self.add_literals("true", "false", "");
+        self.add_soft_keywords(["union", "default", "macro_rules"]);
+        self.add_operators(["<<=", ">>=", "..=", "...", "::", "->", "=>", "..",
+                            "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "+=",
+                            "-=", "*=", "/=", "%=", "&=", "^=", "|="]);
+        self.add_specialchars(['{', '}', '[', ']', '(', ')', '=', '!', '<', '>',
+                               '&', '^', '|', '+', '-', '/', '%', '*', ';', '?',
+                               ':', ',', '.', '~', '#', '@', '$', '\'']);
+        self.set_new_line_as_token(false);
+        self.comments = AllComments;
+        self.nestedComments = true;
+        self.quoteChars = ~['"'];
+        assert!(self.add_rule(~"char",
+                              "b?'([^'\\\\\\n]|\\\\(x[0-9a-fA-F]{2}|u\\{[0-9a-fA-F]{1,6}\\}|.))'",
+                              0).is_ok());
+        assert!(self.add_rule(~"lifetime", "'[A-Za-z_][A-Za-z0-9_]*", 0).is_ok());
+        self.hexNumbers = true;
+        self.exponentNumbers = true;
+        self.digitSeparator = Some('_');
     }
 
     /**
-    * Try to find new tokens.
+    * Private function, load the Python language profile.
     *
-    * Return true if there is token, false otherwise.
+    * The strings can be triple quoted and prefixed ( r / u / b / f and their
+    * combinations ), the f strings are interpolated.
     */
-    pub fn has_token(&mut self) -> bool {
-        if self.pos == self.datas.len() {
-            return false;
-        }
-        else {
-            if !self.clean_for_next_token() {
-                return false;
-            } 
-            if self.is_special_char(self.datas[self.pos]) {
-                self.specialChar = self.datas[self.pos];
-                self.token = SpecialChar;
-                self.pos += 1;
-                return true;
-            }
-            else {
-                let tstr = self.get_word();
-                self.word = tstr;
-                if self.is_number() {
-                    self.token = Number;
-                    self.number = self.word.clone();
-               }
-                else if self.is_keyword() {
-                    self.token = KeyWord;
-                    self.keyword = self.word.clone();
-                }
-                else {
-                    self.token = Word;
-                }
-                return true;
-            }
+    fn load_python_profile(&mut self) -> () {
+        self.add_literals("True", "False", "None");
+        self.add_keywords(["and", "as", "assert", "async",
+                           "await", "break", "class", "continue", "def", "del",
+                           "elif", "else", "except", "finally", "for", "from",
+                           "global", "if", "import", "in", "is", "lambda",
+                           "nonlocal", "not", "or", "pass", "raise", "return",
+                           "try", "while", "with", "yield"]);
+        self.add_soft_keywords(["match", "case", "type"]);
+        self.add_operators(["**=", "//=", "<<=", ">>=", "**", "//", "<<", ">>",
+                            "<=", ">=", "==", "!=", "->", ":=", "+=", "-=", "*=",
+                            "/=", "%=", "&=", "^=", "|=", "@="]);
+        self.add_specialchars(['{', '}', '[', ']', '(', ')', '=', '!', '<', '>',
+                               '&', '^', '|', '+', '-', '/', '%', '*', ';',
+                               ':', ',', '.', '~', '@']);
+        self.set_new_line_as_token(true);
+        self.set_line_continuation(Some(~"\\"));
+        self.singleComment = ~"#";
+        self.offsideRule = true;
+        self.add_interpolated_string(~"f", '"', ~"{", ~"}");
+        self.add_interpolated_string(~"f", '\'', ~"{", ~"}");
+        for ["r", "u", "b", "f", "br", "rb", "fr", "rf"].iter().advance |prefix| {
+            self.add_string_prefix(prefix.to_owned());
         }
+        self.tripleQuotes = true;
+        self.hexNumbers = true;
+        self.exponentNumbers = true;
+        self.digitSeparator = Some('_');
     }
-}
 
-/**
-* Implementation of trait Clone, clone a StringTokenizer instance
-*/
-impl Clone for StringTokenizer {
-    fn clone(&self) -> StringTokenizer {
-        StringTokenizer {
-            datas : self.datas.clone(),
-            pos : self.pos,
-            keyWords : self.keyWords.clone(),
-            delimiters : self.delimiters.clone(),
-            specialChars : self.specialChars.clone(),
-            comments : self.comments,
-            returnIsToken : true,
-            ignoreEscapeChar : false,
-            multiCommentBegin : self.singleComment.clone(),
-            multiCommentEnd : self.singleComment.clone(),
-            singleComment : self.singleComment.clone(),
-            token : NoToken,
-            number : self.number.clone(),
+    /**
+    * Private function, load the JavaScript language profile.
+    */
+    fn load_javascript_profile(&mut self) -> () {
+        self.add_keywords(["async", "await", "break", "case", "catch", "class",
+                           "const", "continue", "debugger", "default", "delete",
+                           "do", "else", "export", "extends", "finally",
+                           "for", "function", "if", "import", "in", "instanceof",
+                           "let", "new", "of", "return", "static", "super",
+                           "switch", "this", "throw", "try", "typeof",
+                           "var", "void", "while", "with", "yield"]);
+        self.add_literals("true", "false", "null");
+        self.add_operators([">>>=", "===", "!==", "**=", "<<=", ">>=", ">>>",
+                            "...", "&&=", "||=", "??=", "=>", "==", "!=", "<=",
+                            ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=",
+                            "*=", "/=", "%=", "&=", "|=", "^=", "**", "<<", ">>"]);
+        self.add_specialchars(['{', '}', '[', ']', '(', ')', '=', '!', '<', '>',
+                               '&', '^', '|', '+', '-', '/', '%', '*', ';', '?',
+                               ':', ',', '.', '~']);
+        self.set_new_line_as_token(false);
+        self.comments = AllComments;
+        self.quoteChars = ~['"', '\'', '`'];
+        self.add_interpolated_string(~"", '`', ~"${", ~"}");
+        self.hexNumbers = true;
+        self.exponentNumbers = true;
+        self.digitSeparator = Some('_');
+    }
+
+    /**
+    * Private function, load the SQL language profile.
+    *
+    * A doubled quote char inside a string is an escaped quote ( 'it''s' ).
+    */
+    fn load_sql_profile(&mut self) -> () {
+        self.add_keywords(["ALL", "ALTER", "AND", "AS", "ASC", "BETWEEN", "BY",
+                           "CASE", "CREATE", "DELETE", "DESC", "DISTINCT", "DROP",
+                           "ELSE", "END", "EXISTS", "FOREIGN", "FROM", "GROUP",
+                           "HAVING", "IN", "INDEX", "INNER", "INSERT", "INTO",
+                           "IS", "JOIN", "KEY", "LEFT", "LIKE", "LIMIT", "NOT",
+                           "OFFSET", "ON", "OR", "ORDER", "OUTER",
+                           "PRIMARY", "REFERENCES", "RIGHT", "SELECT", "SET",
+                           "TABLE", "THEN", "UNION", "UPDATE", "VALUES", "WHEN",
+                           "WHERE"]);
+        self.add_literals("TRUE", "FALSE", "NULL");
+        self.caseInsensitiveKeywords = true;
+        self.add_operators(["<>", "<=", ">=", "!=", "||", "::"]);
+        self.add_specialchars(['(', ')', '=', '<', '>', '+', '-', '/', '%', '*',
+                               ';', ',', '.', '!', '|', ':']);
+        self.set_new_line_as_token(false);
+        self.comments = CComments;
+        self.singleComment = ~"--";
+        self.ignoreEscapeChar = true;
+        self.doubledQuotes = true;
+        self.exponentNumbers = true;
+    }
+
+    /**
+    * Private function, load the Shell language profile.
+    */
+    fn load_shell_profile(&mut self) -> () {
+        self.add_keywords(["case", "coproc", "do", "done", "elif", "else", "esac",
+                           "fi", "for", "function", "if", "in", "select", "then",
+                           "time", "until", "while"]);
+        self.add_operators(["<<<", "&&", "||", ";;", ">>", "<<", ">&", "<&", "&>",
+                            "|&", "=="]);
+        self.add_specialchars(['{', '}', '[', ']', '(', ')', '=', '<', '>', '&',
+                               '|', ';', '$', '`']);
+        self.add_numbered_sigil('$');
+        self.set_new_line_as_token(true);
+        self.set_line_continuation(Some(~"\\"));
+        self.singleComment = ~"#";
+    }
+
+    /**
+    * Private function, load the JSON profile.
+    */
+    fn load_json_profile(&mut self) -> () {
+        self.add_literals("true", "false", "null");
+        self.add_specialchars(['{', '}', '[', ']', ':', ',', '-']);
+        self.set_new_line_as_token(false);
+        self.quoteChars = ~['"'];
+        self.exponentNumbers = true;
+        self.signedNumbers = true;
+    }
+
+    /**
+    * Private function, load the CSS profile.
+    *
+    * The '-', '.' and '#' chars are kept in the words ( font-size / .main / #fff ).
+    */
+    fn load_css_profile(&mut self) -> () {
+        self.add_keywords(["!important"]);
+        self.add_operators(["~=", "|=", "^=", "$=", "*="]);
+        self.add_specialchars(['{', '}', '[', ']', '(', ')', ':', ';', ',', '>',
+                               '+', '~', '=', '*', '/']);
+        self.add_units(["px", "em", "rem", "ex", "ch", "vw", "vh", "vmin", "vmax", "cm",
+                        "mm", "in", "pt", "pc", "deg", "rad", "turn", "s", "ms", "Hz",
+                        "kHz", "dpi", "fr", "%"]);
+        self.set_new_line_as_token(false);
+        self.comments = CComments;
+    }
+
+    /**
+    * Private function, load the Lisp profile ( Common Lisp / Scheme / Clojure ).
+    *
+    * The '-', '?', '!' and '*' chars are kept in the symbols ( foo-bar / nil? / set! /
+    * *out* ) and are special chars elsewhere.
+    */
+    fn load_lisp_profile(&mut self) -> () {
+        self.add_keywords(["cond", "def", "define", "defmacro", "defn", "defun", "do",
+                           "fn", "if", "lambda", "let", "quote", "set!", "setq"]);
+        self.add_literals("true", "false", "nil");
+        self.add_specialchars(['(', ')', '[', ']', '{', '}', '\'', '`', ',', '@',
+                               '-', '?', '!', '*', '+', '/', '<', '>', '=']);
+        self.add_identifier_chars(['-', '?', '!', '*']);
+        self.set_new_line_as_token(false);
+        self.quoteChars = ~['"'];
+        self.singleComment = ~";";
+        self.signedNumbers = true;
+    }
+
+    /**
+    * Load the settings of the StringTokenizer from a TOML document.
+    *
+    * The document is a list of `key = value` lines, the value is a string,
+    * a boolean or a list of strings. The datas are kept.
+    *
+    * # Keys
+    * * profile - The name of a profile loaded before the others settings
+    *   ( "c" / "rust" / "python" / "javascript" / "sql" / "shell" / "json" / "css"
+    *   / "lisp" )
+    * * keywords / soft_keywords / operators - Lists of strings
+    * * literals - A list with the spelling and the value ( "true" / "false" / "null" )
+    *   of each literal
+    * * delimiters / special_chars / quote_chars / sigils / numbered_sigils
+    *   / identifier_chars - Lists of one char strings
+    * * comments - "none" / "c" / "c++" / "all"
+    * * single_line_comment - A string
+    * * multi_line_comment - A list with the begin and the end of the comment
+    * * new_line_as_token / unicode_new_lines / collapse_blank_lines / offside_rule
+    *   / lossless / ignore_escape_char / triple_quoted_strings / doubled_quotes
+    *   / nested_comments / hex_numbers / exponent_numbers
+    *   / signed_numbers / versions / ip_addresses / dotted_numbers / date_times
+    *   / urls / emails / paths / case_insensitive_keywords - Booleans
+    * * line_continuation - A string, empty for no line continuation
+    * * digit_separator - A one char string, empty for no separator
+    * * units / string_prefixes - Lists of strings
+    * * interpolated_strings - A list with the prefix, the quote, the begin and
+    *   the end of each interpolated string
+    * * rules - A list with the name, the regular expression and the priority
+    *   of each custom rule
+    *
+    * # Arguments
+    * * document - The TOML document to load
+    *
+    * Return an error pointing at the faulty key if the document is not valid,
+    * the settings are left untouched in this case.
+    */
+    pub fn load_settings_from_toml(&mut self, document : &str) -> Result<(), SettingsError> {
+        let mut st = StringTokenizer::new_settings_tokenizer(document);
+        let mut settings = ~[];
+
+        while st.has_token() {
+            match st.token {
+                Newline => {}
+                Word    => {
+                    let key = st.word.clone();
+                    if !st.has_token() || !st.is_current_char('=') {
+                        return Err(settings_error(key, "expected '=' after the key"));
+                    }
+                    match st.parse_toml_value(key) {
+                        Ok(value)   => settings.push((key.clone(), value)),
+                        Err(error)  => return Err(error)
+                    }
+                    if st.has_token() && !st.is_current_newline() {
+                        return Err(settings_error(key, "expected a new line after the value"));
+                    }
+                }
+                _       => return Err(settings_error("", "expected a key"))
+            }
+        }
+        self.apply_settings(settings)
+    }
+
+    /**
+    * Load the settings of the StringTokenizer from a JSON document.
+    *
+    * The document is an object using the same keys than a TOML settings
+    * document ( see load_settings_from_toml ). The datas are kept.
+    *
+    * # Arguments
+    * * document - The JSON document to load
+    *
+    * Return an error pointing at the faulty key if the document is not valid,
+    * the settings are left untouched in this case.
+    */
+    pub fn load_settings_from_json(&mut self, document : &str) -> Result<(), SettingsError> {
+        let mut settings = ~[];
+        let root = match json::from_str(document) {
+            Ok(root)    => root,
+            Err(error)  => return Err(settings_error("", fmt!("%u:%u: %s",
+                                                             error.line, error.col, *error.msg)))
+        };
+
+        match root {
+            json::Object(object)    => {
+                for object.iter().advance |(key, value)| {
+                    let setting = match *value {
+                        json::String(ref value)     => StringSetting(value.clone()),
+                        json::Boolean(value)        => BoolSetting(value),
+                        json::List(ref values)      => {
+                            let mut strs = ~[];
+                            for values.iter().advance |value| {
+                                match *value {
+                                    json::String(ref value) => strs.push(value.clone()),
+                                    _                       => {
+                                        return Err(settings_error(*key, "expected a list of strings"));
+                                    }
+                                }
+                            }
+                            ListSetting(strs)
+                        }
+                        _                           => {
+                            return Err(settings_error(*key, "expected a string, a boolean or a list of strings"));
+                        }
+                    };
+                    settings.push((key.clone(), setting));
+                }
+            }
+            _                       => return Err(settings_error("", "expected an object"))
+        }
+        self.apply_settings(settings)
+    }
+
+    /**
+    * Save the settings of the StringTokenizer to a TOML document.
+    *
+    * Return the TOML document.
+    */
+    pub fn settings_to_toml(&self) -> ~str {
+        let settings = self.get_settings();
+        let mut document = ~"";
+
+        for settings.iter().advance |setting| {
+            let (ref key, ref value) = *setting;
+            document.push_str(fmt!("%s = %s\n", *key, value.to_document()));
+        }
+        document
+    }
+
+    /**
+    * Save the settings of the StringTokenizer to a JSON document.
+    *
+    * Return the JSON document.
+    */
+    pub fn settings_to_json(&self) -> ~str {
+        let settings = self.get_settings();
+        let mut lines = ~[];
+
+        for settings.iter().advance |setting| {
+            let (ref key, ref value) = *setting;
+            lines.push(fmt!("    %s: %s", quote_setting(*key), value.to_document()));
+        }
+        fmt!("{\n%s\n}\n", lines.connect(",\n"))
+    }
+
+    /**
+    * Private function, create the StringTokenizer used to read a TOML document.
+    */
+    fn new_settings_tokenizer(document : &str) -> StringTokenizer {
+        let mut st = StringTokenizer::new_with_str(~"");
+
+        st.reset_settings();
+        st.add_delimiters([' ', '\t', '\r']);
+        st.add_specialchars(['[', ']', '=', ',']);
+        st.set_new_line_as_token(true);
+        st.add_keywords(["true", "false"]);
+        st.set_single_line_custom_comment(~"#");
+        st.set_datas_with_str(document.to_owned());
+        st
+    }
+
+    /**
+    * Private function, test if the current token is a given special char.
+    */
+    fn is_current_char(&self, testChar : char) -> bool {
+        match self.token {
+            SpecialChar => self.specialChar == testChar,
+            _           => false
+        }
+    }
+
+    /**
+    * Private function, test if the current token is a Newline.
+    */
+    fn is_current_newline(&self) -> bool {
+        match self.token {
+            Newline => true,
+            _       => false
+        }
+    }
+
+    /**
+    * Private function, read the value of a key in a TOML document.
+    */
+    fn parse_toml_value(&mut self, key : &str) -> Result<SettingValue, SettingsError> {
+        let mut values = ~[];
+        let mut needValue = true;
+
+        if !self.has_token() {
+            return Err(settings_error(key, "missing value"));
+        }
+        match self.token {
+            KeyWord                                 => return Ok(BoolSetting(self.keyword == ~"true")),
+            Word                                    => {
+                match unquote_setting(self.word) {
+                    Some(value) => return Ok(StringSetting(value)),
+                    None        => return Err(settings_error(key, "expected a quoted string"))
+                }
+            }
+            SpecialChar if self.specialChar == '['  => {}
+            _                                       => {
+                return Err(settings_error(key, "expected a string, a boolean or a list of strings"));
+            }
+        }
+        while self.has_token() {
+            match self.token {
+                Newline                                                 => {}
+                SpecialChar if self.specialChar == ']'                  => return Ok(ListSetting(values)),
+                SpecialChar if self.specialChar == ',' && !needValue    => needValue = true,
+                Word if needValue                                       => {
+                    match unquote_setting(self.word) {
+                        Some(value) => values.push(value),
+                        None        => return Err(settings_error(key, "expected a list of quoted strings"))
+                    }
+                    needValue = false;
+                }
+                _                                                       => {
+                    return Err(settings_error(key, "expected a list of quoted strings"));
+                }
+            }
+        }
+        return Err(settings_error(key, "unterminated list"));
+    }
+
+    /**
+    * Private function, get the settings of the StringTokenizer as key / value pairs.
+    */
+    fn get_settings(&self) -> ~[(~str, SettingValue)] {
+        let comments = match self.comments {
+            CplusplusComments   => ~"c++",
+            CComments           => ~"c",
+            AllComments         => ~"all",
+            NoComments          => ~"none"
+        };
+        let separator = match self.digitSeparator {
+            Some(separator) => str::from_char(separator),
+            None            => ~""
+        };
+        let continuation = match self.lineContinuation {
+            Some(ref continuation)  => continuation.clone(),
+            None                    => ~""
+        };
+        let mut keywords = ~[];
+        let mut softKeywords = ~[];
+        let mut literals = ~[];
+        let mut interpolated = ~[];
+        let mut rules = ~[];
+
+        for self.keyWords.iter().enumerate().advance |(i, keyword)| {
+            match self.keywordKinds[i] {
+                (_, ContextualKeyword)  => softKeywords.push(keyword.clone()),
+                _                       => keywords.push(keyword.clone())
+            }
+        }
+        for self.literals.iter().advance |&(ref literal, value)| {
+            literals.push(literal.clone());
+            literals.push(match value {
+                Some(true)  => ~"true",
+                Some(false) => ~"false",
+                None        => ~"null"
+            });
+        }
+        for self.interpolatedStrings.iter().advance |string| {
+            interpolated.push(string.prefix.clone());
+            interpolated.push(str::from_char(string.quote));
+            interpolated.push(string.begin.clone());
+            interpolated.push(string.end.clone());
+        }
+        for self.rules.iter().advance |rule| {
+            rules.push(rule.name.clone());
+            rules.push(rule.pattern.clone());
+            rules.push(rule.priority.to_str());
+        }
+        ~[(~"keywords", ListSetting(keywords)),
+          (~"soft_keywords", ListSetting(softKeywords)),
+          (~"literals", ListSetting(literals)),
+          (~"delimiters", ListSetting(chars_to_settings(self.delimiters))),
+          (~"special_chars", ListSetting(chars_to_settings(self.specialChars))),
+          (~"operators", ListSetting(self.operators.clone())),
+          (~"comments", StringSetting(comments)),
+          (~"single_line_comment", StringSetting(self.singleComment.clone())),
+          (~"multi_line_comment", ListSetting(~[self.multiCommentBegin.clone(),
+                                                self.multiCommentEnd.clone()])),
+          (~"new_line_as_token", BoolSetting(self.returnIsToken)),
+          (~"unicode_new_lines", BoolSetting(self.unicodeNewLines)),
+          (~"collapse_blank_lines", BoolSetting(self.collapseBlankLines)),
+          (~"line_continuation", StringSetting(continuation)),
+          (~"offside_rule", BoolSetting(self.offsideRule)),
+          (~"lossless", BoolSetting(self.lossless)),
+          (~"quote_chars", ListSetting(chars_to_settings(self.quoteChars))),
+          (~"ignore_escape_char", BoolSetting(self.ignoreEscapeChar)),
+          (~"string_prefixes", ListSetting(self.stringPrefixes.clone())),
+          (~"triple_quoted_strings", BoolSetting(self.tripleQuotes)),
+          (~"doubled_quotes", BoolSetting(self.doubledQuotes)),
+          (~"nested_comments", BoolSetting(self.nestedComments)),
+          (~"hex_numbers", BoolSetting(self.hexNumbers)),
+          (~"exponent_numbers", BoolSetting(self.exponentNumbers)),
+          (~"signed_numbers", BoolSetting(self.signedNumbers)),
+          (~"versions", BoolSetting(self.versions)),
+          (~"ip_addresses", BoolSetting(self.ipAddresses)),
+          (~"dotted_numbers", BoolSetting(self.dottedNumbers)),
+          (~"date_times", BoolSetting(self.dateTimes)),
+          (~"urls", BoolSetting(self.urls)),
+          (~"emails", BoolSetting(self.emails)),
+          (~"paths", BoolSetting(self.paths)),
+          (~"case_insensitive_keywords", BoolSetting(self.caseInsensitiveKeywords)),
+          (~"digit_separator", StringSetting(separator)),
+          (~"units", ListSetting(self.units.clone())),
+          (~"sigils", ListSetting(chars_to_settings(self.sigils))),
+          (~"numbered_sigils", ListSetting(chars_to_settings(self.numberedSigils))),
+          (~"identifier_chars", ListSetting(chars_to_settings(self.identifierChars))),
+          (~"interpolated_strings", ListSetting(interpolated)),
+          (~"rules", ListSetting(rules))]
+    }
+
+    /**
+    * Private function, apply settings read from a document.
+    *
+    * The profile is applied first, then the others keys override it.
+    * Nothing is modified if one of the settings is not valid.
+    */
+    fn apply_settings(&mut self, settings : ~[(~str, SettingValue)]) -> Result<(), SettingsError> {
+        let keys = ["profile", "keywords", "soft_keywords", "literals", "delimiters",
+                    "special_chars", "operators", "comments", "single_line_comment",
+                    "multi_line_comment",
+                    "new_line_as_token", "unicode_new_lines", "collapse_blank_lines",
+                    "line_continuation", "offside_rule", "lossless", "quote_chars",
+                    "ignore_escape_char", "string_prefixes", "triple_quoted_strings",
+                    "doubled_quotes", "nested_comments", "hex_numbers", "exponent_numbers",
+                    "signed_numbers",
+                    "versions", "ip_addresses", "dotted_numbers", "date_times", "urls",
+                    "emails", "paths", "digit_separator",
+                    "case_insensitive_keywords", "units", "sigils", "numbered_sigils",
+                    "identifier_chars", "interpolated_strings", "rules"];
+        let mut updated = self.clone();
+
+        for settings.iter().advance |setting| {
+            let (ref key, _) = *setting;
+            if !keys.iter().any(|known| *known == key.as_slice()) {
+                return Err(settings_error(*key, "unknown setting"));
+            }
+        }
+        for keys.iter().advance |known| {
+            for settings.iter().advance |setting| {
+                let (ref key, ref value) = *setting;
+                if *known == key.as_slice() {
+                    match updated.apply_setting(*known, value) {
+                        Ok(())      => {}
+                        Err(error)  => return Err(error)
+                    }
+                }
+            }
+        }
+        updated.compiled = false;
+        *self = updated;
+        Ok(())
+    }
+
+    /**
+    * Private function, apply one setting read from a document.
+    */
+    fn apply_setting(&mut self, key : &str, value : &SettingValue) -> Result<(), SettingsError> {
+        match key {
+            "profile"               => match value.get_string(key) {
+                Ok(name)    => match profile_from_name(name) {
+                    Some(profile)   => { self.set_profile(profile); Ok(()) }
+                    None            => Err(settings_error(key, "unknown profile"))
+                },
+                Err(error)  => Err(error)
+            },
+            "keywords"              => match value.get_list(key) {
+                Ok(keywords)    => { self.replace_keywords(keywords, false); Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "soft_keywords"         => match value.get_list(key) {
+                Ok(keywords)    => { self.replace_keywords(keywords, true); Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "literals"              => match value.get_list(key) {
+                Ok(literals)    => {
+                    let mut i = 0;
+                    if literals.len() % 2 != 0 {
+                        return Err(settings_error(key, "expected a spelling and a value by literal"));
+                    }
+                    self.literals.clear();
+                    while i < literals.len() {
+                        match literals[i + 1].as_slice() {
+                            "true"  => self.add_bool_literal(literals[i].clone(), true),
+                            "false" => self.add_bool_literal(literals[i].clone(), false),
+                            "null"  => self.add_null_literal(literals[i].clone()),
+                            _       => return Err(settings_error(key, "expected \"true\", \"false\" or \"null\""))
+                        }
+                        i += 2;
+                    }
+                    Ok(())
+                }
+                Err(error)      => Err(error)
+            },
+            "delimiters"            => match value.get_chars(key) {
+                Ok(delimiters)  => { self.delimiters = delimiters; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "special_chars"         => match value.get_chars(key) {
+                Ok(specials)    => { self.specialChars = specials; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "operators"             => match value.get_list(key) {
+                Ok(operators)   => { self.operators = operators; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "comments"              => match value.get_string(key) {
+                Ok(name)        => match name.as_slice() {
+                    "none"  => { self.comments = NoComments; Ok(()) }
+                    "c"     => { self.comments = CComments; Ok(()) }
+                    "c++"   => { self.comments = CplusplusComments; Ok(()) }
+                    "all"   => { self.comments = AllComments; Ok(()) }
+                    _       => Err(settings_error(key, "expected \"none\", \"c\", \"c++\" or \"all\""))
+                },
+                Err(error)      => Err(error)
+            },
+            "single_line_comment"   => match value.get_string(key) {
+                Ok(comment)     => { self.singleComment = comment; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "multi_line_comment"    => match value.get_list(key) {
+                Ok(comment)     => {
+                    if comment.len() != 2 {
+                        return Err(settings_error(key, "expected the begin and the end of the comment"));
+                    }
+                    self.multiCommentBegin = comment[0].clone();
+                    self.multiCommentEnd = comment[1].clone();
+                    Ok(())
+                }
+                Err(error)      => Err(error)
+            },
+            "new_line_as_token"     => match value.get_bool(key) {
+                Ok(isToken)     => { self.set_new_line_as_token(isToken); Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "unicode_new_lines"     => match value.get_bool(key) {
+                Ok(allow)       => { self.set_unicode_new_lines(allow); Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "collapse_blank_lines"  => match value.get_bool(key) {
+                Ok(collapse)    => { self.collapse_blank_lines(collapse); Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "line_continuation"     => match value.get_string(key) {
+                Ok(continuation)    => {
+                    if continuation.len() == 0 {
+                        self.set_line_continuation(None);
+                    }
+                    else {
+                        self.set_line_continuation(Some(continuation));
+                    }
+                    Ok(())
+                }
+                Err(error)          => Err(error)
+            },
+            "offside_rule"          => match value.get_bool(key) {
+                Ok(offside)     => { self.set_offside_rule(offside); Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "lossless"              => match value.get_bool(key) {
+                Ok(lossless)    => { self.set_lossless(lossless); Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "quote_chars"           => match value.get_chars(key) {
+                Ok(quotes)      => { self.quoteChars = quotes; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "ignore_escape_char"    => match value.get_bool(key) {
+                Ok(ignore)      => { self.ignoreEscapeChar = ignore; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "string_prefixes"       => match value.get_list(key) {
+                Ok(prefixes)    => { self.stringPrefixes = prefixes; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "triple_quoted_strings" => match value.get_bool(key) {
+                Ok(allow)       => { self.tripleQuotes = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "doubled_quotes"        => match value.get_bool(key) {
+                Ok(allow)       => { self.doubledQuotes = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "nested_comments"       => match value.get_bool(key) {
+                Ok(allow)       => { self.nestedComments = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "hex_numbers"           => match value.get_bool(key) {
+                Ok(allow)       => { self.hexNumbers = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "exponent_numbers"      => match value.get_bool(key) {
+                Ok(allow)       => { self.exponentNumbers = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "signed_numbers"        => match value.get_bool(key) {
+                Ok(allow)       => { self.signedNumbers = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "versions"              => match value.get_bool(key) {
+                Ok(allow)       => { self.versions = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "ip_addresses"          => match value.get_bool(key) {
+                Ok(allow)       => { self.ipAddresses = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "dotted_numbers"        => match value.get_bool(key) {
+                Ok(allow)       => { self.dottedNumbers = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "date_times"            => match value.get_bool(key) {
+                Ok(allow)       => { self.dateTimes = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "urls"                  => match value.get_bool(key) {
+                Ok(allow)       => { self.urls = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "emails"                => match value.get_bool(key) {
+                Ok(allow)       => { self.emails = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "paths"                 => match value.get_bool(key) {
+                Ok(allow)       => { self.paths = allow; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "case_insensitive_keywords" => match value.get_bool(key) {
+                Ok(insensitive) => { self.set_case_insensitive_keywords(insensitive); Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "digit_separator"       => match value.get_string(key) {
+                Ok(separator)   => {
+                    match separator.char_len() {
+                        0   => { self.digitSeparator = None; Ok(()) }
+                        1   => { self.digitSeparator = Some(separator.char_at(0)); Ok(()) }
+                        _   => Err(settings_error(key, "expected a one char string"))
+                    }
+                }
+                Err(error)      => Err(error)
+            },
+            "units"                 => match value.get_list(key) {
+                Ok(units)       => { self.units = units; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "sigils"                => match value.get_chars(key) {
+                Ok(sigils)      => { self.sigils = sigils; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "numbered_sigils"       => match value.get_chars(key) {
+                Ok(sigils)      => { self.numberedSigils = sigils; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "identifier_chars"      => match value.get_chars(key) {
+                Ok(chars)       => { self.identifierChars = chars; Ok(()) }
+                Err(error)      => Err(error)
+            },
+            "interpolated_strings"  => match value.get_list(key) {
+                Ok(strings)     => {
+                    let mut i = 0;
+                    if strings.len() % 4 != 0 {
+                        return Err(settings_error(key, "expected a prefix, a quote, a begin and an end by string"));
+                    }
+                    self.interpolatedStrings.clear();
+                    while i < strings.len() {
+                        if strings[i + 1].char_len() != 1 {
+                            return Err(settings_error(key, "expected a one char quote"));
+                        }
+                        self.add_interpolated_string(strings[i].clone(), strings[i + 1].char_at(0),
+                                                     strings[i + 2].clone(), strings[i + 3].clone());
+                        i += 4;
+                    }
+                    Ok(())
+                }
+                Err(error)      => Err(error)
+            },
+            "rules"                 => match value.get_list(key) {
+                Ok(rules)       => {
+                    let mut i = 0;
+                    if rules.len() % 3 != 0 {
+                        return Err(settings_error(key, "expected a name, a regex and a priority by rule"));
+                    }
+                    self.rules.clear();
+                    while i < rules.len() {
+                        let priority = match int::from_str(rules[i + 2]) {
+                            Some(priority)  => priority,
+                            None            => return Err(settings_error(key, "expected an integer priority"))
+                        };
+                        match self.add_rule(rules[i].clone(), rules[i + 1], priority) {
+                            Ok(())      => {}
+                            Err(error)  => return Err(settings_error(key, error))
+                        }
+                        i += 3;
+                    }
+                    Ok(())
+                }
+                Err(error)      => Err(error)
+            },
+            _                       => Err(settings_error(key, "unknown setting"))
+        }
+    }
+
+    /**
+    * Private function, replace the keywords or the soft keywords read from a document.
+    *
+    * The keywords of the other kind are kept, the keywords are added again
+    * before the soft keywords so their ids stay their positions.
+    *
+    * # Arguments
+    * * keywords - The new keywords
+    * * soft - true to replace the soft keywords, false to replace the others
+    */
+    fn replace_keywords(&mut self, keywords : ~[~str], soft : bool) -> () {
+        let mut kept = ~[];
+
+        for self.keyWords.iter().enumerate().advance |(i, keyword)| {
+            let isSoft = match self.keywordKinds[i] {
+                (_, ContextualKeyword)  => true,
+                _                       => false
+            };
+            if isSoft != soft {
+                kept.push(keyword.clone());
+            }
+        }
+        let (hard, softs) = if soft { (kept, keywords) } else { (keywords, kept) };
+        self.keyWords.clear();
+        self.keywordKinds.clear();
+        for hard.iter().advance |keyword| {
+            self.add_keyword(keyword.clone());
+        }
+        for softs.iter().advance |keyword| {
+            self.add_soft_keyword(keyword.clone());
+        }
+    }
+
+    /**
+    * Set the type of comments handled by the StringTokenizer.
+    *
+    * # Default
+    * * No comments are handled by default 
+    *
+    * # Arguments
+    * * comments - The new type of comments to handle
+    */
+    pub fn set_comments(&mut self, comments : CommentsTypes) -> () {
+        self.compiled = false;
+        self.comments = comments;
+    }
+
+    /**
+    * Define if the line breaks are Newline tokens or delimiters
+    *
+    * The line breaks are "\n", "\r\n", "\r" and U+2028 if set_unicode_new_lines
+    * is used.
+    *
+    * # Default
+    * * By default a line break is a token
+    * 
+    * # Arguments
+    * isToken - true if it's a token false otherwise
+    */
+    pub fn set_new_line_as_token(&mut self, isToken : bool) -> () {
+        self.returnIsToken = isToken;
+    }
+
+    /**
+    * Define if the U+2028 char ( line separator ) is a line break.
+    *
+    * # Default
+    * * By default U+2028 is not a line break
+    *
+    * # Arguments
+    * * allow - true if U+2028 is a line break, false otherwise
+    */
+    pub fn set_unicode_new_lines(&mut self, allow : bool) -> () {
+        self.unicodeNewLines = allow;
+        self.join_lines();
+    }
+
+    /**
+    * Define if the consecutive blank lines provide only one Newline token.
+    *
+    * The newline attribute of the token contain all the collapsed lines.
+    *
+    * # Default
+    * * By default each line break is a Newline token
+    *
+    * # Arguments
+    * * collapse - true if the blank lines are collapsed, false otherwise
+    */
+    pub fn collapse_blank_lines(&mut self, collapse : bool) -> () {
+        self.collapseBlankLines = collapse;
+    }
+    
+    /**
+    * Define if the indentation of the lines provide Indent and Dedent tokens
+    * ( offside rule ).
+    *
+    * At the begin of each line who is not blank the indentation is compared with
+    * the indentation of the previous lines. A deeper indentation provide an Indent
+    * token, a shallower one provide a Dedent token for each closed block. The
+    * blocks still opened at the end of the datas are closed by Dedent tokens.
+    *
+    * An IndentError token is provided if the indentation mix tabs and spaces or
+    * if a dedent does not match any previous indentation.
+    *
+    * The line breaks stay tokens or delimiters, see set_new_line_as_token. Inside
+    * the brackets ( see set_bracket_pairs ) the lines are joined, they don't
+    * provide any Indent, Dedent or Newline token.
+    *
+    * # Default
+    * * By default the offside rule is not used
+    *
+    * # Arguments
+    * * offside - true if the indentation provide tokens, false otherwise
+    */
+    pub fn set_offside_rule(&mut self, offside : bool) -> () {
+        self.offsideRule = offside;
+    }
+
+    /**
+    * Ignore or not the escape char.
+    *
+    * # Default
+    * * By default escape char is not ignored
+    *
+    * # Example
+    * * ignored - This string |"This is a message :\"Hello World\""| provide these tokens |"This is a message : \" - Hello - World\ - ""|
+    * * not ignored - This string |"This is a message :\"Hello World\""| provide this tokens |"This is a message :\"Hello World\""|
+    * 
+    * # Arguments
+    * * ignore - Ture if escape char must be ignored, false otherwise
+    */
+    pub fn ignore_escape_char(&mut self, ignore : bool) -> () {
+        self.ignoreEscapeChar = ignore
+    }
+
+    /**
+    * Add a new string prefix to the StringTokenizer dictionnary.
+    *
+    * A prefix just before a quote char is read with the string, the prefixes
+    * are compared without regard to the case. The interpolated strings
+    * are found before the prefixed strings.
+    *
+    * # Example
+    * * With ~"rb" the string |Rb'\d+'| provide the token |Rb'\d+'|
+    *
+    * # Arguments
+    * * prefix - The prefix to add
+    */
+    pub fn add_string_prefix(&mut self, prefix : ~str) -> () {
+        self.stringPrefixes.push(prefix);
+    }
+
+    /**
+    * Define if three quote chars begin a string ended by the same three chars.
+    *
+    * The quote char alone and the line breaks are kept in a triple quoted
+    * string, the interpolated strings can also be triple quoted.
+    *
+    * # Default
+    * * By default the triple quoted strings are not allowed
+    *
+    * # Arguments
+    * * allow - true to allow the triple quoted strings, false otherwise
+    */
+    pub fn allow_triple_quoted_strings(&mut self, allow : bool) -> () {
+        self.tripleQuotes = allow;
+    }
+
+    /**
+    * Define if a doubled quote char inside a string is an escaped quote char.
+    *
+    * # Default
+    * * By default a quote char always end the string
+    *
+    * # Example
+    * * With doubled quotes the string |'it''s'| provide the token |'it''s'|
+    *
+    * # Arguments
+    * * allow - true to read the doubled quote chars as escaped quotes, false otherwise
+    */
+    pub fn allow_doubled_quotes(&mut self, allow : bool) -> () {
+        self.doubledQuotes = allow;
+    }
+
+    /**
+    * Define if the C comments and the custom multi line comments can be nested.
+    *
+    * # Default
+    * * By default a comment end at the first end marker
+    *
+    * # Example
+    * * With nested comments |/* a /* b */ c */| is one comment
+    *
+    * # Arguments
+    * * allow - true to count the nested comments, false otherwise
+    */
+    pub fn allow_nested_comments(&mut self, allow : bool) -> () {
+        self.nestedComments = allow;
+    }
+
+    /**
+    * Add a new lexer mode.
+    *
+    * A new mode use the default dictionnary, select it with select_mode to
+    * change its keywords, delimiters, special chars, operators and comments.
+    * The first mode is named "default".
+    *
+    * # Arguments
+    * * name - The name of the new mode
+    *
+    * Return false if a mode with the same name already exists, true otherwise.
+    */
+    pub fn add_mode(&mut self, name : ~str) -> bool {
+        if self.find_mode(name).is_some() {
+            return false;
+        }
+        let mut st = StringTokenizer::new(~[]);
+        self.modes.push(LexerMode {
+            name : name,
+            settings : Some(st.take_settings()),
+            transitions : ~[]
+        });
+        return true;
+    }
+
+    /**
+    * Select the current lexer mode, without using the modes stack.
+    *
+    * The settings functions ( add_keyword, add_delimiter... ) and
+    * add_mode_transition apply to the current mode.
+    *
+    * # Arguments
+    * * name - The name of the mode
+    *
+    * Return false if the mode doesn't exist, true otherwise.
+    */
+    pub fn select_mode(&mut self, name : &str) -> bool {
+        match self.find_mode(name) {
+            Some(mode)  => {
+                self.switch_mode(mode);
+                return true;
+            }
+            None        => return false
+        }
+    }
+
+    /**
+    * Add a transition to the current lexer mode.
+    *
+    * When a token whose text is the trigger is read in the current mode,
+    * PushMode enter a mode and remember the current one, PopMode return
+    * to the previous mode. The token itself belongs to the mode who read it.
+    *
+    * # Arguments
+    * * trigger - The text of the token who trigger the transition
+    * * transition - The change of mode
+    *
+    * Return false if the mode to push doesn't exist, true otherwise.
+    */
+    pub fn add_mode_transition(&mut self, trigger : ~str, transition : ModeTransition) -> bool {
+        match transition {
+            PushMode(ref name) if self.find_mode(*name).is_none()   => return false,
+            _                                                       => {}
+        }
+        let current = self.currentMode;
+        self.modes[current].transitions.push((trigger, transition));
+        return true;
+    }
+
+    /**
+    * Enter a lexer mode, the current mode is pushed on the modes stack.
+    *
+    * # Arguments
+    * * name - The name of the mode
+    *
+    * Return false if the mode doesn't exist, true otherwise.
+    */
+    pub fn push_mode(&mut self, name : &str) -> bool {
+        match self.find_mode(name) {
+            Some(mode)  => {
+                self.modeStack.push(self.currentMode);
+                self.switch_mode(mode);
+                return true;
+            }
+            None        => return false
+        }
+    }
+
+    /**
+    * Return to the lexer mode on the top of the modes stack.
+    *
+    * Return false if the modes stack is empty, true otherwise.
+    */
+    pub fn pop_mode(&mut self) -> bool {
+        if self.modeStack.is_empty() {
+            return false;
+        }
+        let mode = self.modeStack.pop();
+        self.switch_mode(mode);
+        return true;
+    }
+
+    /**
+    * Get the name of the current lexer mode.
+    */
+    pub fn get_mode(&self) -> ~str {
+        self.modes[self.currentMode].name.clone()
+    }
+
+    /**
+    * Private function, find a lexer mode by its name.
+    */
+    fn find_mode(&self, name : &str) -> Option<uint> {
+        for self.modes.iter().enumerate().advance |(i, mode)| {
+            if mode.name.as_slice() == name {
+                return Some(i);
+            }
+        }
+        return None
+    }
+
+    /**
+    * Private function, move the dictionnary of the current mode out of the StringTokenizer.
+    */
+    fn take_settings(&mut self) -> ModeSettings {
+        ModeSettings {
+            keyWords : util::replace(&mut self.keyWords, ~[]),
+            keywordKinds : util::replace(&mut self.keywordKinds, ~[]),
+            literals : util::replace(&mut self.literals, ~[]),
+            caseInsensitiveKeywords : self.caseInsensitiveKeywords,
+            delimiters : util::replace(&mut self.delimiters, ~[]),
+            specialChars : util::replace(&mut self.specialChars, ~[]),
+            operators : util::replace(&mut self.operators, ~[]),
+            quoteChars : util::replace(&mut self.quoteChars, ~[]),
+            hexNumbers : self.hexNumbers,
+            exponentNumbers : self.exponentNumbers,
+            signedNumbers : self.signedNumbers,
+            versions : self.versions,
+            ipAddresses : self.ipAddresses,
+            dottedNumbers : self.dottedNumbers,
+            dateTimes : self.dateTimes,
+            urls : self.urls,
+            emails : self.emails,
+            paths : self.paths,
+            digitSeparator : self.digitSeparator,
+            units : util::replace(&mut self.units, ~[]),
+            sigils : util::replace(&mut self.sigils, ~[]),
+            numberedSigils : util::replace(&mut self.numberedSigils, ~[]),
+            identifierChars : util::replace(&mut self.identifierChars, ~[]),
+            rules : util::replace(&mut self.rules, ~[]),
+            interpolatedStrings : util::replace(&mut self.interpolatedStrings, ~[]),
+            offsideRule : self.offsideRule,
+            comments : self.comments,
+            returnIsToken : self.returnIsToken,
+            unicodeNewLines : self.unicodeNewLines,
+            collapseBlankLines : self.collapseBlankLines,
+            ignoreEscapeChar : self.ignoreEscapeChar,
+            stringPrefixes : util::replace(&mut self.stringPrefixes, ~[]),
+            tripleQuotes : self.tripleQuotes,
+            doubledQuotes : self.doubledQuotes,
+            nestedComments : self.nestedComments,
+            multiCommentBegin : util::replace(&mut self.multiCommentBegin, ~""),
+            multiCommentEnd : util::replace(&mut self.multiCommentEnd, ~""),
+            singleComment : util::replace(&mut self.singleComment, ~""),
+            compiled : self.compiled,
+            charTable : util::replace(&mut self.charTable, CharTable::new()),
+            lexicon : util::replace(&mut self.lexicon, Lexicon::new())
+        }
+    }
+
+    /**
+    * Private function, move the dictionnary of a mode into the StringTokenizer.
+    */
+    fn put_settings(&mut self, settings : ModeSettings) -> () {
+        let ModeSettings {
+            keyWords, keywordKinds, caseInsensitiveKeywords, literals, delimiters, specialChars,
+            operators, quoteChars, hexNumbers, exponentNumbers, signedNumbers, versions,
+            ipAddresses, dottedNumbers, dateTimes, urls, emails, paths, digitSeparator, units, sigils,
+            numberedSigils, identifierChars, rules,
+            interpolatedStrings, offsideRule, comments, returnIsToken, unicodeNewLines,
+            collapseBlankLines, ignoreEscapeChar, stringPrefixes, tripleQuotes, doubledQuotes,
+            nestedComments, multiCommentBegin, multiCommentEnd, singleComment,
+            compiled, charTable, lexicon
+        } = settings;
+
+        self.keyWords = keyWords;
+        self.keywordKinds = keywordKinds;
+        self.literals = literals;
+        self.caseInsensitiveKeywords = caseInsensitiveKeywords;
+        self.delimiters = delimiters;
+        self.specialChars = specialChars;
+        self.operators = operators;
+        self.quoteChars = quoteChars;
+        self.hexNumbers = hexNumbers;
+        self.exponentNumbers = exponentNumbers;
+        self.signedNumbers = signedNumbers;
+        self.versions = versions;
+        self.ipAddresses = ipAddresses;
+        self.dottedNumbers = dottedNumbers;
+        self.dateTimes = dateTimes;
+        self.urls = urls;
+        self.emails = emails;
+        self.paths = paths;
+        self.digitSeparator = digitSeparator;
+        self.units = units;
+        self.sigils = sigils;
+        self.numberedSigils = numberedSigils;
+        self.identifierChars = identifierChars;
+        self.rules = rules;
+        self.interpolatedStrings = interpolatedStrings;
+        self.offsideRule = offsideRule;
+        self.comments = comments;
+        self.returnIsToken = returnIsToken;
+        self.unicodeNewLines = unicodeNewLines;
+        self.collapseBlankLines = collapseBlankLines;
+        self.ignoreEscapeChar = ignoreEscapeChar;
+        self.stringPrefixes = stringPrefixes;
+        self.tripleQuotes = tripleQuotes;
+        self.doubledQuotes = doubledQuotes;
+        self.nestedComments = nestedComments;
+        self.multiCommentBegin = multiCommentBegin;
+        self.multiCommentEnd = multiCommentEnd;
+        self.singleComment = singleComment;
+        self.compiled = compiled;
+        self.charTable = charTable;
+        self.lexicon = lexicon;
+        // the matches of the mode were found in the datas of its last use
+        self.lexicon.forget();
+    }
+
+    /**
+    * Private function, make a lexer mode the current one.
+    */
+    fn switch_mode(&mut self, mode : uint) -> () {
+        if mode == self.currentMode {
+            return;
+        }
+        let current = self.currentMode;
+        let saved = self.take_settings();
+        self.modes[current].settings = Some(saved);
+        let loaded = util::replace(&mut self.modes[mode].settings, None);
+        self.put_settings(loaded.unwrap());
+        self.currentMode = mode;
+    }
+
+    /**
+    * Private function, go back to the first mode of the modes stack.
+    */
+    fn reset_modes(&mut self) -> () {
+        if !self.modeStack.is_empty() {
+            let first = self.modeStack[0];
+            self.switch_mode(first);
+            self.modeStack.clear();
+        }
+    }
+
+    /**
+    * Private function, apply the transition of the current mode triggered
+    * by the current token.
+    */
+    fn apply_mode_transition(&mut self) -> () {
+        let mut found = None;
+
+        if self.modes[self.currentMode].transitions.is_empty() {
+            return;
+        }
+        let text = self.get_token_text();
+        for self.modes[self.currentMode].transitions.iter().advance |transition| {
+            let (ref trigger, ref change) = *transition;
+            if found.is_none() && *trigger == text {
+                found = Some(change.clone());
+            }
+        }
+        match found {
+            Some(PushMode(name))    => { self.push_mode(name); }
+            Some(PopMode)           => { self.pop_mode(); }
+            None                    => {}
+        }
+    }
+
+    /**
+    * Private function, get the text of the current token.
+    */
+    fn get_token_text(&self) -> ~str {
+        match self.token {
+            Word            => self.word.clone(),
+            KeyWord         => self.keyword.clone(),
+            Number          => self.number.clone(),
+            SpecialChar     => str::from_char(self.specialChar),
+            Operator        => self.operator.clone(),
+            Custom          => self.custom.clone(),
+            StrStart | StrFragment | InterpStart | InterpEnd | StrEnd
+                            => self.stringPart.clone(),
+            Indent | Dedent | IndentError
+                            => ~"",
+            Newline         => self.newline.clone(),
+            EndOfFile       => ~"",
+            Bool | Null     => self.literal.clone(),
+            Dimension       => self.number + self.unit,
+            Version | IpAddress | DottedNumber
+                            => self.dotted.clone(),
+            Date | Time | DateTime | Duration
+                            => self.dateTime.clone(),
+            Url | Email | Path
+                            => self.location.clone(),
+            Sigiled         => str::from_char(self.sigil) + self.name,
+            NoToken         => ~""
+        }
+    }
+
+    /**
+    * Reset all the settings contained on the StringTokenizer
+    * ( datas / keywords / delimiters / specialchars / operators / modes / tokens ).
+    */
+    pub fn reset_settings(&mut self) -> () {
+        self.reset_modes();
+        self.currentMode = 0;
+        self.modes = ~[LexerMode {
+            name : DEFAULT_MODE.to_owned(),
+            settings : None,
+            transitions : ~[]
+        }];
+        self.mode = DEFAULT_MODE.to_owned();
+        self.compiled = false;
+        self.delimiters.clear();
+        self.keyWords.clear();
+        self.keywordKinds.clear();
+        self.literals.clear();
+        self.caseInsensitiveKeywords = false;
+        self.datas.clear();
+        self.source.clear();
+        self.physical.clear();
+        self.lineContinuation = None;
+        self.lossless = false;
+        self.triviaEnd = 0;
+        self.endOfDatas = false;
+        self.pos = 0;
+        self.specialChars.clear();
+        self.operators.clear();
+        self.quoteChars = ~['"', '\''];
+        self.hexNumbers = false;
+        self.exponentNumbers = false;
+        self.signedNumbers = false;
+        self.versions = false;
+        self.ipAddresses = false;
+        self.dottedNumbers = false;
+        self.dateTimes = false;
+        self.urls = false;
+        self.emails = false;
+        self.paths = false;
+        self.digitSeparator = None;
+        self.units.clear();
+        self.sigils.clear();
+        self.numberedSigils.clear();
+        self.identifierChars.clear();
+        self.rules.clear();
+        self.interpolatedStrings.clear();
+        self.interpolations.clear();
+        self.offsideRule = false;
+        self.reset_indentation();
+        self.bracketPairs = ~[('(', ')'), ('[', ']'), ('{', '}')];
+        self.comments = NoComments;
+        self.returnIsToken = true;
+        self.unicodeNewLines = false;
+        self.collapseBlankLines = false;
+        self.stringPrefixes.clear();
+        self.tripleQuotes = false;
+        self.doubledQuotes = false;
+        self.nestedComments = false;
+        self.token = NoToken;
+        self.number = ~"";
+        self.unit = ~"";
+        self.dotted = ~"";
+        self.dateTime = ~"";
+        self.timestamp = new_timestamp();
+        self.duration = 0;
+        self.location = ~"";
+        self.sigil = 0 as char;
+        self.name = ~"";
+        self.word = ~"";
+        self.keyword = ~"";
+        self.keywordId = 0;
+        self.keywordCategory = OtherKeyword;
+        self.maybeKeyword = false;
+        self.literal = ~"";
+        self.boolean = false;
+        self.softKeywordPredicate = None;
+        self.history.clear();
+        self.multiCommentBegin = ~"";
+        self.multiCommentEnd = ~"";
+        self.singleComment = ~"";
+        self.specialChar = 0 as char;
+        self.operator = ~"";
+        self.custom = ~"";
+        self.customKind = ~"";
+        self.stringPart = ~"";
+        self.indentation = ~"";
+        self.indentError = ~"";
+        self.newline = ~"";
+        self.span = Span { begin : 0, end : 0 };
+        self.leadingTrivia = ~"";
+        self.trailingTrivia = ~"";
+    }
+
+    /**
+    * Set a custom multi line comment
+    *
+    * # Arguments
+    * * commentBegin - The string who represent the begin of the comment.
+    * * commentEnd - The string who represent the end of the comment.
+    */
+    pub fn set_multi_line_custom_comment(&mut self, commentBegin : ~str, commentEnd : ~str) -> () {
+        self.compiled = false;
+        self.multiCommentBegin = commentBegin;
+        self.multiCommentEnd = commentEnd;
+    }
+    
+    /**
+    * Set a custom single line comment.
+    *
+    * # Arguments
+    * * commentBegin - The str who represent the begin of a custom comment
+    */
+    pub fn set_single_line_custom_comment(&mut self, commentBegin : ~str) -> () {
+        self.compiled = false;
+        self.singleComment = commentBegin;
+    }
+    
+    /**
+    * Private function, retrieve a string between two cotes.
+    *
+    * # Arguments
+    * * cote - The char who open and close the string
+    *
+    * Return the string contained between the two cotes, cotes included.
+    */
+    fn get_cote_string(&mut self, cote : char) -> ~str {
+        let begin = self.pos;
+        let quotes = self.quotes_len(self.pos, cote);
+        let mut escaped = false;
+
+        self.pos += quotes;
+        while self.pos < self.datas.len() {
+            let current = self.datas[self.pos];
+            self.pos += 1;
+            if escaped {
+                escaped = false;
+            }
+            else if current == '\\' && !self.ignoreEscapeChar {
+                escaped = true;
+            }
+            else if current == cote && quotes == 3 {
+                if self.pos + 1 < self.datas.len() && self.datas[self.pos] == cote
+                    && self.datas[self.pos + 1] == cote {
+                    self.pos += 2;
+                    break;
+                }
+            }
+            else if current == cote && self.doubledQuotes && self.pos < self.datas.len()
+                && self.datas[self.pos] == cote {
+                self.pos += 1;
+            }
+            else if current == cote {
+                break;
+            }
+        }
+        str::from_chars(self.datas.slice(begin, self.pos))
+    }
+
+    /**
+    * Private function, count the quote chars who begin a string at a position.
+    *
+    * Return 3 for a triple quoted string if they are allowed, 1 otherwise.
+    */
+    fn quotes_len(&self, pos : uint, cote : char) -> uint {
+        if self.tripleQuotes && pos + 2 < self.datas.len()
+            && self.datas[pos] == cote && self.datas[pos + 1] == cote
+            && self.datas[pos + 2] == cote {
+            3
+        }
+        else {
+            1
+        }
+    }
+
+    /**
+    * Private function, find a string prefix followed by a quote char at the current position.
+    *
+    * Return the length of the longest prefix, 0 if there is no prefix.
+    */
+    fn string_prefix_len(&self) -> uint {
+        let mut found = 0;
+
+        for self.stringPrefixes.iter().advance |prefix| {
+            let len = prefix.char_len();
+            let end = self.pos + len;
+            if len > found && end < self.datas.len() && self.is_quote_char(self.datas[end])
+                && fold_str(str::from_chars(self.datas.slice(self.pos, end)).as_slice())
+                   == fold_str(*prefix) {
+                found = len;
+            }
+        }
+        found
+    }
+
+    /**
+    * Private function, get the next from the current position.
+    *
+    * Return the word read.
+    */
+    fn get_word(&mut self) -> ~str {
+        let mut tstr : ~[char] = ~[];
+        let prefix = self.string_prefix_len();
+
+        if prefix > 0 || self.is_quote_char(self.datas[self.pos]) {
+            let begin = self.pos;
+            let cote = self.datas[self.pos + prefix];
+            self.pos += prefix;
+            let string = self.get_cote_string(cote);
+            return str::from_chars(self.datas.slice(begin, begin + prefix)) + string;
+        }
+        while self.pos < self.datas.len()
+            && !self.is_delimiter(self.datas[self.pos])
+            && self.line_break_len(self.pos) == 0
+            && (!self.is_special_char(self.datas[self.pos])
+                || self.continue_number(tstr) || self.continue_identifier(tstr)) {
+            tstr.push(self.datas[self.pos]);
+            self.pos += 1;
+        }
+        str::from_chars(tstr)
+    }
+
+    /**
+    * Private function, check if the special char at the current position
+    * is part of the identifier being read ( foo-bar or --custom-prop ).
+    *
+    * # Arguments
+    * * tstr - The chars already read
+    *
+    * Return true if the special char continue the identifier, false otherwise
+    */
+    fn continue_identifier(&self, tstr : &[char]) -> bool {
+        if !self.identifierChars.contains(&self.datas[self.pos]) {
+            return false;
+        }
+        if tstr.len() == 0 {
+            return self.begin_identifier();
+        }
+        !tstr[0].is_digit()
+    }
+
+    /**
+    * Private function, test if the identifier chars at the current position
+    * are followed by a letter and begin an identifier.
+    */
+    fn begin_identifier(&self) -> bool {
+        let mut i = self.pos;
+
+        while i < self.datas.len() && self.identifierChars.contains(&self.datas[i]) {
+            i += 1;
+        }
+        if i == self.pos || i == self.datas.len() {
+            return false;
+        }
+        let next = self.datas[i];
+        !self.is_special_char(next) && !self.is_delimiter(next)
+            && (is_ascii_letter(next) || next == '_' || ((next as uint) > 127 && next.is_alphabetic()))
+    }
+
+    /**
+    * Private function, check if the special char at the current position
+    * is part of the number being read ( 3.14 or 1e-10 ).
+    *
+    * # Arguments
+    * * tstr - The chars already read
+    *
+    * Return true if the special char continue the number, false otherwise
+    */
+    fn continue_number(&self, tstr : &[char]) -> bool {
+        let current = self.datas[self.pos];
+
+        if tstr.len() == 0 || !tstr[0].is_digit() {
+            return false;
+        }
+        if tstr.len() > 1 && (tstr[1] == 'x' || tstr[1] == 'X') {
+            return false;
+        }
+        if current == '.' {
+            for tstr.iter().advance |schar| {
+                if *schar == '.' || *schar == 'e' || *schar == 'E' {
+                    return false;
+                }
+            }
+            return self.pos + 1 < self.datas.len() && self.datas[self.pos + 1].is_digit();
+        }
+        if self.exponentNumbers && (current == '-' || current == '+') {
+            let last = tstr[tstr.len() - 1];
+            return last == 'e' || last == 'E';
+        }
+        return false
+    }
+
+    /**
+    * Private function, test if the current position is inside the text of
+    * an interpolated string.
+    */
+    fn in_interpolated_string(&self) -> bool {
+        let len = self.interpolations.len();
+        len > 0 && self.interpolations[len - 1].inString
+    }
+
+    /**
+    * Private function, read the next part of the interpolated string being read.
+    *
+    * Return true, the token is a StrEnd, an InterpStart or a StrFragment.
+    */
+    fn get_string_part(&mut self) -> bool {
+        let top = self.interpolations.len() - 1;
+        let quote = self.interpolations[top].string.quote;
+        let end = str::from_chars(vec::from_elem(self.interpolations[top].quotes, quote));
+        let begin = self.interpolations[top].string.begin.clone();
+        let start = self.pos;
+        let mut escaped = false;
+
+        if self.matches_at(self.pos, end) {
+            self.pos += end.char_len();
+            self.interpolations.pop();
+            self.stringPart = end;
+            self.token = StrEnd;
+            return true;
+        }
+        if self.matches_at(self.pos, begin) {
+            self.pos += begin.char_len();
+            self.interpolations[top].inString = false;
+            self.interpolations[top].depth = 0;
+            self.stringPart = begin;
+            self.token = InterpStart;
+            return true;
+        }
+        while self.pos < self.datas.len() {
+            let current = self.datas[self.pos];
+            if escaped {
+                escaped = false;
+            }
+            else if current == '\\' && !self.ignoreEscapeChar {
+                escaped = true;
+            }
+            else if self.matches_at(self.pos, end) || self.matches_at(self.pos, begin) {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.stringPart = str::from_chars(self.datas.slice(start, self.pos));
+        self.token = StrFragment;
+        return true;
+    }
+
+    /**
+    * Private function, find the end of an interpolation or the begin of an
+    * interpolated string at the current position.
+    *
+    * Return true if one is found, the token is an InterpEnd or a StrStart.
+    */
+    fn get_interpolation_token(&mut self) -> bool {
+        let len = self.interpolations.len();
+        let mut found = None;
+
+        if len > 0 && self.interpolations[len - 1].depth == 0 {
+            let end = self.interpolations[len - 1].string.end.clone();
+            if self.matches_at(self.pos, end) {
+                self.pos += end.char_len();
+                self.interpolations[len - 1].inString = true;
+                self.stringPart = end;
+                self.token = InterpEnd;
+                return true;
+            }
+        }
+        for self.interpolatedStrings.iter().advance |string| {
+            let quotePos = self.pos + string.prefix.char_len();
+            if found.is_none() && quotePos < self.datas.len()
+                && self.datas[quotePos] == string.quote
+                && (string.prefix.len() == 0 || self.matches_at(self.pos, string.prefix)) {
+                found = Some(string.clone());
+            }
+        }
+        match found {
+            Some(string)    => {
+                let quotes = self.quotes_len(self.pos + string.prefix.char_len(), string.quote);
+                self.pos += string.prefix.char_len() + quotes;
+                self.stringPart = string.prefix
+                    + str::from_chars(vec::from_elem(quotes, string.quote));
+                self.interpolations.push(Interpolation {
+                    string : string,
+                    quotes : quotes,
+                    inString : true,
+                    depth : 0
+                });
+                self.token = StrStart;
+                return true;
+            }
+            None            => return false
+        }
+    }
+
+    /**
+    * Private function, count the brackets opened and closed by the current
+    * token inside an interpolation.
+    */
+    fn track_interpolation_depth(&mut self) -> () {
+        let len = self.interpolations.len();
+
+        if len == 0 || self.interpolations[len - 1].inString {
+            return;
+        }
+        match self.token {
+            StrStart | StrFragment | InterpStart | InterpEnd | StrEnd   => return,
+            _                                                           => {}
+        }
+        let text = self.get_token_text();
+        if text == self.interpolations[len - 1].string.open {
+            self.interpolations[len - 1].depth += 1;
+        }
+        else if text == self.interpolations[len - 1].string.end
+            && self.interpolations[len - 1].depth > 0 {
+            self.interpolations[len - 1].depth -= 1;
+        }
+    }
+
+    /**
+    * Private function, read a line break and the blank lines following it
+    * if they are collapsed.
+    *
+    * Return true if a line break is found, the token is a Newline.
+    */
+    fn get_newline(&mut self) -> bool {
+        let begin = self.pos;
+        let mut len = self.line_break_len(self.pos);
+        let mut blank = true;
+
+        if len == 0 {
+            return false;
+        }
+        self.pos += len;
+        while self.collapseBlankLines && blank {
+            let mut next = self.pos;
+            while next < self.datas.len() && self.is_delimiter(self.datas[next])
+                && self.line_break_len(next) == 0 {
+                next += 1;
+            }
+            len = self.line_break_len(next);
+            if len == 0 {
+                blank = false;
+            }
+            else {
+                self.pos = next + len;
+            }
+        }
+        self.newline = str::from_chars(self.datas.slice(begin, self.pos));
+        self.token = Newline;
+        return true;
+    }
+
+    /**
+    * Private function, forget the indentation of the lines already read.
+    */
+    fn reset_indentation(&mut self) -> () {
+        self.indentStack.clear();
+        self.pendingDedents = 0;
+        self.indentLine = None;
+        self.bracketDepth = 0;
+    }
+
+    /**
+    * Private function, count the brackets opened by the special chars read.
+    *
+    * With the offside rule the lines are joined inside the brackets, like the
+    * implicit line joining of Python.
+    */
+    fn track_bracket_depth(&mut self) -> () {
+        if self.token != SpecialChar {
+            return;
+        }
+        for self.bracketPairs.iter().advance |&(open, close)| {
+            if self.specialChar == open {
+                self.bracketDepth += 1;
+            }
+            else if self.specialChar == close && self.bracketDepth > 0 {
+                self.bracketDepth -= 1;
+            }
+        }
+    }
+
+    /**
+    * Private function, find the indentation of the line of the current position.
+    *
+    * Return the begin of the line and its indentation, None if the current
+    * position is not the first token of the line.
+    */
+    fn line_indentation(&self) -> Option<(uint, ~str)> {
+        let mut lineBegin = self.pos;
+
+        while lineBegin > 0 && !self.is_line_break(self.datas[lineBegin - 1]) {
+            lineBegin -= 1;
+        }
+        if self.indentLine == Some(lineBegin) || self.line_break_len(self.pos) > 0 {
+            return None;
+        }
+        for self.datas.slice(lineBegin, self.pos).iter().advance |ichar| {
+            if *ichar != ' ' && *ichar != '\t' {
+                return None;
+            }
+        }
+        Some((lineBegin, str::from_chars(self.datas.slice(lineBegin, self.pos))))
+    }
+
+    /**
+    * Private function, compare the indentation of a new line with the
+    * indentation of the opened blocks.
+    *
+    * Return true if the line provide a token, the token is an Indent,
+    * a Dedent or an IndentError.
+    */
+    fn get_indentation_token(&mut self) -> bool {
+        if self.bracketDepth > 0 {
+            return false;
+        }
+        let (lineBegin, indentation) = match self.line_indentation() {
+            Some(found) => found,
+            None        => return false
+        };
+        let current = match self.indentStack.last_opt() {
+            Some(current)   => current.clone(),
+            None            => ~""
+        };
+
+        self.indentLine = Some(lineBegin);
+        self.indentation = indentation.clone();
+        if indentation.contains_char(' ') && indentation.contains_char('\t') {
+            self.indentError = ~"mixed tabs and spaces in indentation";
+            self.token = IndentError;
+            return true;
+        }
+        if indentation == current {
+            return false;
+        }
+        if indentation.starts_with(current) {
+            self.indentStack.push(indentation);
+            self.token = Indent;
+            return true;
+        }
+        if !current.starts_with(indentation) {
+            self.indentError = ~"mixed tabs and spaces in indentation";
+            self.token = IndentError;
+            return true;
+        }
+        let mut closed = 0;
+        let mut i = self.indentStack.len();
+        while i > 0 && self.indentStack[i - 1].len() > indentation.len() {
+            closed += 1;
+            i -= 1;
+        }
+        let outer = if i > 0 { self.indentStack[i - 1].clone() } else { ~"" };
+        if outer != indentation {
+            self.indentError = ~"dedent does not match any outer indentation";
+            self.token = IndentError;
+            return true;
+        }
+        self.indentStack.truncate(i);
+        self.pendingDedents = closed - 1;
+        self.token = Dedent;
+        return true;
+    }
+
+    /**
+    * Private function, provide the Dedent tokens waiting to be read.
+    *
+    * # Arguments
+    * * end - true if the end of the datas is reached, all the blocks are closed
+    *
+    * Return true if the token is a Dedent, false otherwise
+    */
+    fn get_dedent(&mut self, end : bool) -> bool {
+        if end && self.offsideRule && self.indentStack.len() > 0 {
+            self.indentStack.pop();
+            self.pendingDedents += 1;
+        }
+        if self.pendingDedents == 0 {
+            return false;
+        }
+        self.pendingDedents -= 1;
+        self.indentation = match self.indentStack.last_opt() {
+            Some(current)   => current.clone(),
+            None            => ~""
+        };
+        self.token = Dedent;
+        return true;
+    }
+
+    /**
+    * Private function, find the best custom rule matching at the begin of the
+    * built-in token just read ( operator, special char or word ).
+    *
+    * The rule replace the built-in token if it match at least as many chars,
+    * with a negative priority it must match more chars.
+    *
+    * # Arguments
+    * * begin - The begin of the built-in token, the current position is its end
+    *
+    * Return true if a custom rule is found, false otherwise
+    */
+    fn get_custom_token(&mut self, begin : uint) -> bool {
+        if self.rules.len() == 0 {
+            return false;
+        }
+        self.scan(begin);
+        let (end, matched) = match self.lexicon.longest(|pattern| match pattern {
+            CustomPattern(_)    => true,
+            _                   => false
+        }) {
+            Some(found) => found,
+            None        => return false
+        };
+        let mut best = None;
+        for matched.iter().advance |pattern| {
+            match (*pattern, best) {
+                (CustomPattern(rule), None)                                 => best = Some(rule),
+                (CustomPattern(rule), Some(current))
+                    if self.rules[rule].priority > self.rules[current].priority => best = Some(rule),
+                _                                                           => {}
+            }
+        }
+        let best = best.unwrap();
+        let len = end - begin;
+        let builtinLen = self.pos - begin;
+        if len < builtinLen || (len == builtinLen && self.rules[best].priority < 0) {
+            return false;
+        }
+        self.customKind = self.rules[best].name.clone();
+        self.custom = str::from_chars(self.datas.slice(begin, end));
+        self.pos = end;
+        return true;
+    }
+
+    /**
+    * Private function, find the longest operator at the current position.
+    *
+    * Return true if an operator is found, false otherwise
+    */
+    fn get_operator(&mut self) -> bool {
+        self.scan(self.pos);
+        let found = self.lexicon.longest(|pattern| match pattern {
+            FixedOperator(_)    => true,
+            _                   => false
+        });
+
+        match found {
+            Some((end, patterns))   => match patterns[0] {
+                FixedOperator(operator) => {
+                    self.operator = self.operators[operator].clone();
+                    self.pos = end;
+                    return true;
+                }
+                _                       => return false
+            },
+            None                    => return false
+        }
+    }
+
+    /**
+    * Private function, handle C++ style comment
+    *
+    * Return true if a comment is found, false otherwise
+    */
+    fn c_plus_plus_comments(&mut self) -> bool {
+        if self.pos + 1 < self.datas.len() {
+            if self.datas[self.pos] == '/'
+                && self.datas[self.pos + 1] == '/' {
+                while self.pos < self.datas.len()
+                    && !self.is_line_break(self.datas[self.pos]) {
+                    self.pos += 1;
+                }
+                return true;
+            }
+                    else {
+                        return false;
+                    }
+                    
+                }
+        else {
+            return false;
+        }
+    }
+    
+    /**
+    * Private function, handle C style comment erasing
+    *
+    * The nested comments are counted if they are allowed.
+    *
+    * Return true if a C style comment is found, false otherwise
+    */
+    fn c_comments(&mut self) -> bool {
+        let mut end = false;
+        let mut depth = 0u;
+        if self.pos + 1 < self.datas.len() {
+            if self.datas[self.pos] == '/'
+                && self.datas[self.pos + 1] == '*' {
+                self.pos += 2;
+                while self.pos < self.datas.len()
+                    && !end {
+                    if self.pos + 1 < self.datas.len()
+                        && (self.datas[self.pos] == '*' && self.datas[self.pos + 1] == '/') {
+                        if depth == 0 {
+                            end = true;
+                        }
+                        else {
+                            depth -= 1;
+                        }
+                        self.pos += 1;
+                    }
+                    else if self.nestedComments && self.pos + 1 < self.datas.len()
+                        && (self.datas[self.pos] == '/' && self.datas[self.pos + 1] == '*') {
+                        depth += 1;
+                        self.pos += 1;
+                    }
+                    self.pos += 1;
+                }
+                return true;
+            }
+            
+            else {
+                return false;
+            }
+        }
+        else {
+            return false;
+        }
+        
+    }
+
+    /**
+    * Private function, handle custom single line comment erasing
+    *
+    * The end of line is not erased.
+    *
+    * Return true if a custom single line comment is found, false otherwise
+    */
+    fn custom_single_line_comments(&mut self) -> bool {
+        if self.matches_at(self.pos, self.singleComment) {
+            while self.pos < self.datas.len()
+                && !self.is_line_break(self.datas[self.pos]) {
+                self.pos += 1;
+            }
+            return true;
+        }
+        else {
+            return false;
+        }
+    }
+
+    /**
+    * Private function, handle custom multi line comment erasing
+    *
+    * The nested comments are counted if they are allowed.
+    *
+    * Return true if a custom multi line comment is found, false otherwise
+    */
+    fn custom_multi_line_comments(&mut self) -> bool {
+        let mut depth = 0u;
+
+        if self.multiCommentEnd.len() > 0
+            && self.matches_at(self.pos, self.multiCommentBegin) {
+            self.pos += self.multiCommentBegin.char_len();
+            while self.pos < self.datas.len()
+                && (depth > 0 || !self.matches_at(self.pos, self.multiCommentEnd)) {
+                if self.matches_at(self.pos, self.multiCommentEnd) {
+                    depth -= 1;
+                    self.pos += self.multiCommentEnd.char_len();
+                }
+                else if self.nestedComments && self.matches_at(self.pos, self.multiCommentBegin) {
+                    depth += 1;
+                    self.pos += self.multiCommentBegin.char_len();
+                }
+                else {
+                    self.pos += 1;
+                }
+            }
+            if self.pos < self.datas.len() {
+                self.pos += self.multiCommentEnd.char_len();
+            }
+            return true;
+        }
+        else {
+            return false;
+        }
+    }
+
+    /**
+    * Private function, Check if there is comments and delete them
+    *
+    * Return true if a comment is found.
+    */
+    fn has_comments(&mut self) -> bool {
+        if self.pos == self.datas.len() {
+            return false;
+        }
+        else {
+            self.scan(self.pos);
+            let found = self.lexicon.longest(|pattern| match pattern {
+                CommentMarker(_)    => true,
+                _                   => false
+            });
+            let tag = match found {
+                Some((_, patterns)) => match patterns[0] {
+                    CommentMarker(tag)  => tag,
+                    _                   => return false
+                },
+                None                => return false
+            };
+            if tag == C_COMMENT_TAG {
+                self.c_comments()
+            }
+            else if tag == CPP_COMMENT_TAG {
+                self.c_plus_plus_comments()
+            }
+            else if tag == MULTI_COMMENT_TAG {
+                self.custom_multi_line_comments()
+            }
+            else {
+                self.custom_single_line_comments()
+            }
+        }
+    }
+    
+    /**
+    * Private function, consume all delimiters or comments
+    */
+    fn clean_for_next_token(&mut self) -> bool {
+        self.consume_delimiters();
+        while self.has_comments() {
+            self.consume_delimiters();
+        }
+        if self.pos == self.datas.len() {
+            false
+        }
+        else {
+            true
+        }
+    }
+
+    /**
+    * Private function, test if an operand can begin at the current position, a
+    * sign or a '/' there begin a signed number or a path instead of an operator.
+    *
+    * Return true if the previous token is an operator, a special char who is not
+    * a closing bracket, or is on a previous line, false otherwise
+    */
+    fn operand_expected(&self) -> bool {
+        let lastEnd = if self.lastTokenEnd < self.pos { self.lastTokenEnd } else { self.pos };
+
+        if lastEnd < self.pos && self.identifierChars.contains(&self.datas[self.pos]) {
+            return true;
+        }
+        for self.datas.slice(lastEnd, self.pos).iter().advance |schar| {
+            if self.is_line_break(*schar) {
+                return true;
+            }
+        }
+        match self.lastToken {
+            NoToken | Newline | Operator | Indent | Dedent | InterpStart    => true,
+            SpecialChar                                                     => {
+                !self.bracketPairs.iter().any(|&(_, close)| close == self.lastSpecialChar)
+            }
+            _                                                               => false
+        }
+    }
+
+    /**
+    * Private function, read a number beginning by a sign.
+    *
+    * Return true if a signed number is found, the token is a Number, false otherwise
+    */
+    fn get_signed_number(&mut self) -> bool {
+        let begin = self.pos;
+        let sign = self.datas[self.pos];
+        let word = self.word.clone();
+
+        if (sign != '-' && sign != '+') || !self.operand_expected() {
+            return false;
+        }
+        self.pos += 1;
+        if self.pos < self.datas.len() && !self.is_delimiter(self.datas[self.pos])
+            && !self.is_special_char(self.datas[self.pos])
+            && !self.is_quote_char(self.datas[self.pos])
+            && self.line_break_len(self.pos) == 0 {
+            self.word = self.get_word();
+            if self.get_number_token() {
+                self.number = str::from_char(sign) + self.number;
+                self.word = str::from_char(sign) + self.word;
+                return true;
+            }
+        }
+        self.pos = begin;
+        self.word = word;
+        return false;
+    }
+
+    /**
+    * Private function, read a sigil followed by an identifier.
+    *
+    * Return true if one is found, the token is a Sigiled, false otherwise
+    */
+    fn get_sigiled_token(&mut self) -> bool {
+        let sigil = self.datas[self.pos];
+        let begin = self.pos + 1;
+        let mut end = begin;
+
+        if !self.sigils.contains(&sigil) || begin == self.datas.len() {
+            return false;
+        }
+        if self.datas[begin].is_digit() {
+            if !self.numberedSigils.contains(&sigil) {
+                return false;
+            }
+            end = begin + 1;
+        }
+        else {
+            while end < self.datas.len() && !self.is_delimiter(self.datas[end])
+                && !self.is_special_char(self.datas[end])
+                && (is_identifier_char(self.datas[end])
+                    || ((self.datas[end] as uint) > 127 && self.datas[end].is_alphabetic())) {
+                end += 1;
+            }
+        }
+        if end == begin {
+            return false;
+        }
+        self.sigil = sigil;
+        self.name = str::from_chars(self.datas.slice(begin, end));
+        self.word = str::from_char(sigil) + self.name;
+        self.token = Sigiled;
+        self.pos = end;
+        true
+    }
+
+    /**
+    * Private function, read an URL, an email address or a path.
+    *
+    * Return true if one is found, the token is an Url, an Email or a Path, false otherwise
+    */
+    fn get_location_token(&mut self) -> bool {
+        let mut found = None;
+
+        if self.urls {
+            found = self.match_url(self.pos).map(|end| (*end, Url));
+        }
+        if found.is_none() && self.emails {
+            found = self.match_email(self.pos).map(|end| (*end, Email));
+        }
+        if found.is_none() && self.paths {
+            found = self.match_path(self.pos).map(|end| (*end, Path));
+        }
+        match found {
+            Some((end, token))  => {
+                self.location = str::from_chars(self.datas.slice(self.pos, end));
+                self.word = self.location.clone();
+                self.token = token;
+                self.pos = end;
+                true
+            }
+            None                => false
+        }
+    }
+
+    /**
+    * Private function, find the end of the chars of an URL or a path beginning at a position.
+    *
+    * The punctuation at the end and the unbalanced closing brackets are not kept.
+    */
+    fn location_end(&self, pos : uint, allowed : &fn(char) -> bool) -> uint {
+        let mut end = pos;
+        let mut depth = 0;
+
+        while end < self.datas.len() && !self.is_delimiter(self.datas[end])
+            && !self.is_quote_char(self.datas[end]) && self.line_break_len(end) == 0
+            && allowed(self.datas[end]) {
+            match self.datas[end] {
+                '(' | '['   => depth += 1,
+                ')' | ']'   => {
+                    if depth == 0 {
+                        break;
+                    }
+                    depth -= 1;
+                }
+                _           => {}
+            }
+            end += 1;
+        }
+        while end > pos && ".,;:!?".contains_char(self.datas[end - 1]) {
+            end -= 1;
+        }
+        end
+    }
+
+    /**
+    * Private function, match an URL at a position.
+    *
+    * Return the end of the URL, None if there is no URL
+    */
+    fn match_url(&self, pos : uint) -> Option<uint> {
+        let mut i = pos;
+
+        if pos > 0 && is_identifier_char(self.datas[pos - 1]) {
+            return None;
+        }
+        while i < self.datas.len() && (is_label_char(self.datas[i]) || "+.".contains_char(self.datas[i])) {
+            i += 1;
+        }
+        if i == pos || !is_ascii_letter(self.datas[pos]) || !self.matches_at(i, "://") {
+            return None;
+        }
+        let end = self.location_end(i + 3, |c| {
+            is_identifier_char(c) || "-.~:/?#[]@!$&'()*+,;=%".contains_char(c)
+        });
+        if end > i + 3 { Some(end) } else { None }
+    }
+
+    /**
+    * Private function, match an email address at a position.
+    *
+    * Return the end of the address, None if there is no address
+    */
+    fn match_email(&self, pos : uint) -> Option<uint> {
+        let mut i = pos;
+        let mut end = pos;
+        let mut labels = 0;
+
+        if !is_identifier_char(self.datas[pos]) || (pos > 0 && is_identifier_char(self.datas[pos - 1])) {
+            return None;
+        }
+        while i < self.datas.len() && (is_identifier_char(self.datas[i])
+                                       || "+-.%".contains_char(self.datas[i])) {
+            i += 1;
+        }
+        if i == self.datas.len() || self.datas[i] != '@' || self.datas[i - 1] == '.' {
+            return None;
+        }
+        i += 1;
+        while i < self.datas.len() {
+            let begin = i;
+            while i < self.datas.len() && is_label_char(self.datas[i]) {
+                i += 1;
+            }
+            if i == begin {
+                break;
+            }
+            labels += 1;
+            end = i;
+            if i + 1 < self.datas.len() && self.datas[i] == '.' {
+                i += 1;
+            }
+            else {
+                break;
+            }
+        }
+        if labels < 2 || (end < self.datas.len()
+                          && (is_identifier_char(self.datas[end]) || self.datas[end] == '@')) {
+            return None;
+        }
+        Some(end)
+    }
+
+    /**
+    * Private function, match a POSIX or Windows path at a position.
+    *
+    * Return the end of the path, None if there is no path
+    */
+    fn match_path(&self, pos : uint) -> Option<uint> {
+        let body = if self.matches_at(pos, "./") || self.matches_at(pos, "~/") {
+            pos + 2
+        }
+        else if self.matches_at(pos, "../") {
+            pos + 3
+        }
+        else if self.matches_at(pos, "/") || self.matches_at(pos, "\\\\") {
+            pos + if self.datas[pos] == '/' { 1 } else { 2 }
+        }
+        else if is_ascii_letter(self.datas[pos])
+            && (self.matches_at(pos + 1, ":\\") || self.matches_at(pos + 1, ":/")) {
+            pos + 3
+        }
+        else {
+            return None;
+        };
+
+        if pos > 0 && (is_identifier_char(self.datas[pos - 1])
+                       || self.bracketPairs.iter().any(|&(_, close)| close == self.datas[pos - 1])) {
+            return None;
+        }
+        if self.datas[pos] == '/' && self.is_special_char('/')
+            && (body == self.datas.len() || !is_identifier_char(self.datas[body])
+                || !self.operand_expected()) {
+            return None;
+        }
+        let end = self.location_end(body, |c| {
+            is_identifier_char(c) || "-.~/\\+@%=".contains_char(c)
+        });
+        if end > body { Some(end) } else { None }
+    }
+
+    /**
+    * Private function, read a date, a time, a timestamp or a duration.
+    *
+    * Return true if one is found, the token is a Date, a Time, a DateTime or a
+    * Duration, false otherwise
+    */
+    #[cfg(date_times)]
+    fn get_date_time_token(&mut self) -> bool {
+        let mut timestamp = new_timestamp();
+        let mut found = None;
+
+        if !self.datas[self.pos].is_digit() {
+            return false;
+        }
+        match self.match_date(self.pos, &mut timestamp) {
+            Some(end)   => {
+                if end < self.datas.len() && (self.datas[end] == 'T' || self.datas[end] == 't') {
+                    found = self.match_time(end + 1, false, &mut timestamp).map(|end| (*end, DateTime));
+                }
+                if found.is_none() && self.is_dotted_end(end) {
+                    found = Some((end, Date));
+                }
+            }
+            None        => {
+                found = self.match_time(self.pos, true, &mut timestamp).map(|end| (*end, Time));
+            }
+        }
+        if found.is_none() {
+            match self.match_duration(self.pos) {
+                Some((end, duration))   => {
+                    self.duration = duration;
+                    found = Some((end, Duration));
+                }
+                None                    => {}
+            }
+        }
+        match found {
+            Some((end, token))  => {
+                self.dateTime = str::from_chars(self.datas.slice(self.pos, end));
+                self.word = self.dateTime.clone();
+                self.timestamp = timestamp;
+                self.token = token;
+                self.pos = end;
+                true
+            }
+            None                => false
+        }
+    }
+
+    /**
+    * Private function, read a number of exactly len digits at a position.
+    *
+    * Return the number, None if there is not enough digits
+    */
+    #[cfg(date_times)]
+    fn fixed_number(&self, pos : uint, len : uint) -> Option<uint> {
+        let mut value = 0;
+
+        if pos + len > self.datas.len() || self.digits_end(pos) != pos + len {
+            return None;
+        }
+        for self.datas.slice(pos, pos + len).iter().advance |digit| {
+            value = value * 10 + digit.to_digit(10).get();
+        }
+        Some(value)
+    }
+
+    /**
+    * Private function, match an ISO 8601 date ( YYYY-MM-DD ) at a position.
+    *
+    * Return the end of the date, None if there is no date
+    */
+    #[cfg(date_times)]
+    fn match_date(&self, pos : uint, timestamp : &mut Timestamp) -> Option<uint> {
+        if !self.matches_at(pos + 4, "-") || !self.matches_at(pos + 7, "-") {
+            return None;
+        }
+        match (self.fixed_number(pos, 4), self.fixed_number(pos + 5, 2), self.fixed_number(pos + 8, 2)) {
+            (Some(year), Some(month), Some(day))
+                if month >= 1 && month <= 12 && day >= 1 && day <= 31 => {
+                timestamp.year = year;
+                timestamp.month = month;
+                timestamp.day = day;
+                Some(pos + 10)
+            }
+            _   => None
+        }
+    }
+
+    /**
+    * Private function, match a time ( HH:MM:SS.fraction ) and its offset
+    * ( Z / +HH:MM ) at a position.
+    *
+    * # Arguments
+    * * pos - The position of the time
+    * * needSeconds - true if the seconds can't be omitted
+    * * timestamp - The Timestamp who receive the components
+    *
+    * Return the end of the time, None if there is no time
+    */
+    #[cfg(date_times)]
+    fn match_time(&self, pos : uint, needSeconds : bool, timestamp : &mut Timestamp) -> Option<uint> {
+        let mut end = pos + 5;
+        let mut second = 0;
+        let mut nanosecond = 0;
+        let mut offset = None;
+        let (hour, minute) = match (self.fixed_number(pos, 2), self.fixed_number(pos + 3, 2)) {
+            (Some(hour), Some(minute)) if self.matches_at(pos + 2, ":") => (hour, minute),
+            _                                                           => return None
+        };
+
+        if self.matches_at(end, ":") {
+            match self.fixed_number(end + 1, 2) {
+                Some(value) => { second = value; end += 3; }
+                None        => return None
+            }
+            if self.matches_at(end, ".") && end + 1 < self.datas.len() && self.datas[end + 1].is_digit() {
+                let fractionEnd = self.digits_end(end + 1);
+                for uint::range(0, 9) |i| {
+                    let digit = if end + 1 + i < fractionEnd {
+                        self.datas[end + 1 + i].to_digit(10).get()
+                    }
+                    else {
+                        0
+                    };
+                    nanosecond = nanosecond * 10 + digit;
+                }
+                end = fractionEnd;
+            }
+        }
+        else if needSeconds {
+            return None;
+        }
+        if hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+        if self.matches_at(end, "Z") || self.matches_at(end, "z") {
+            offset = Some(0);
+            end += 1;
+        }
+        else if self.matches_at(end, "+") || self.matches_at(end, "-") {
+            let sign = if self.datas[end] == '-' { -1 } else { 1 };
+            let offsetMinutes = if self.matches_at(end + 3, ":") { end + 4 } else { end + 3 };
+            match (self.fixed_number(end + 1, 2), self.fixed_number(offsetMinutes, 2)) {
+                (Some(hours), Some(minutes)) if hours <= 23 && minutes <= 59   => {
+                    offset = Some(sign * (hours * 60 + minutes) as int);
+                    end = offsetMinutes + 2;
+                }
+                _                                                               => {}
+            }
+        }
+        if !self.is_dotted_end(end) {
+            return None;
+        }
+        timestamp.hour = hour;
+        timestamp.minute = minute;
+        timestamp.second = second;
+        timestamp.nanosecond = nanosecond;
+        timestamp.offset = offset;
+        Some(end)
+    }
+
+    /**
+    * Private function, match a duration ( 1h30m / 1.5s / 500ms ) at a position.
+    *
+    * Return the end of the duration and its number of nanoseconds, None if there
+    * is no duration
+    */
+    #[cfg(date_times)]
+    fn match_duration(&self, pos : uint) -> Option<(uint, u64)> {
+        let mut i = pos;
+        let mut total = 0u64;
+
+        while i < self.datas.len() && self.datas[i].is_digit() {
+            let mut end = self.digits_end(i);
+            let mut value = 0u64;
+            let mut fraction = 0u64;
+            let mut scale = 1u64;
+            let mut factor = None;
+            for self.datas.slice(i, end).iter().advance |digit| {
+                value = value * 10 + digit.to_digit(10).get() as u64;
+            }
+            if end + 1 < self.datas.len() && self.datas[end] == '.' && self.datas[end + 1].is_digit() {
+                let fractionEnd = self.digits_end(end + 1);
+                for self.datas.slice(end + 1, fractionEnd).iter().advance |digit| {
+                    fraction = fraction * 10 + digit.to_digit(10).get() as u64;
+                    scale *= 10;
+                }
+                end = fractionEnd;
+            }
+            for DURATION_UNITS.iter().advance |&(unit, unitFactor)| {
+                if factor.is_none() && self.matches_at(end, unit) {
+                    factor = Some((unit.len(), unitFactor));
+                }
+            }
+            match factor {
+                Some((len, unitFactor)) => {
+                    total += value * unitFactor + fraction * unitFactor / scale;
+                    i = end + len;
+                }
+                None                    => return None
+            }
+        }
+        if i > pos && self.is_dotted_end(i) { Some((i, total)) } else { None }
+    }
+
+    /**
+    * Private function, the dates, times and durations are not recognized
+    * without `--cfg date_times`.
+    *
+    * Return false
+    */
+    #[cfg(not(date_times))]
+    fn get_date_time_token(&mut self) -> bool {
+        false
+    }
+
+    /**
+    * Private function, read a version, an IP address or a dotted number.
+    *
+    * Return true if one is found, the token is a Version, an IpAddress or a
+    * DottedNumber, false otherwise
+    */
+    fn get_dotted_token(&mut self) -> bool {
+        let current = self.datas[self.pos];
+        let mut found = None;
+
+        if !self.versions && !self.ipAddresses && !self.dottedNumbers {
+            return false;
+        }
+        if self.ipAddresses && (current == ':' || current.to_digit(16).is_some()) {
+            found = self.match_ipv6(self.pos).map(|end| (*end, IpAddress));
+        }
+        if current.is_digit() {
+            if found.is_none() && self.ipAddresses {
+                found = self.match_ipv4(self.pos).map(|end| (*end, IpAddress));
+            }
+            if found.is_none() && self.versions {
+                found = self.match_version(self.pos).map(|end| (*end, Version));
+            }
+            if found.is_none() && self.dottedNumbers {
+                found = self.match_dotted_number(self.pos).map(|end| (*end, DottedNumber));
+            }
+        }
+        match found {
+            Some((end, token))  => {
+                self.dotted = str::from_chars(self.datas.slice(self.pos, end));
+                self.word = self.dotted.clone();
+                self.token = token;
+                self.pos = end;
+                true
+            }
+            None                => false
+        }
+    }
+
+    /**
+    * Private function, find the end of the digits beginning at a position.
+    */
+    fn digits_end(&self, pos : uint) -> uint {
+        let mut end = pos;
+
+        while end < self.datas.len() && self.datas[end].is_digit() {
+            end += 1;
+        }
+        end
+    }
+
+    /**
+    * Private function, test if a dotted token can end at a position.
+    *
+    * Return false if the position is followed by a letter, a digit, or a dot and a digit
+    */
+    fn is_dotted_end(&self, end : uint) -> bool {
+        if end == self.datas.len() {
+            return true;
+        }
+        let next = self.datas[end];
+        if is_identifier_char(next) {
+            return false;
+        }
+        !(next == '.' && end + 1 < self.datas.len() && self.datas[end + 1].is_digit())
+    }
+
+    /**
+    * Private function, match an IPv4 address at a position.
+    *
+    * Return the end of the address, None if there is no address
+    */
+    fn match_ipv4(&self, pos : uint) -> Option<uint> {
+        let mut i = pos;
+
+        for uint::range(0, 4) |group| {
+            let end = self.digits_end(i);
+            let mut value = 0;
+            if end == i || end - i > 3 {
+                return None;
+            }
+            for self.datas.slice(i, end).iter().advance |digit| {
+                value = value * 10 + digit.to_digit(10).get();
+            }
+            if value > 255 {
+                return None;
+            }
+            i = end;
+            if group < 3 {
+                if i >= self.datas.len() || self.datas[i] != '.' {
+                    return None;
+                }
+                i += 1;
+            }
+        }
+        if self.is_dotted_end(i) { Some(i) } else { None }
+    }
+
+    /**
+    * Private function, match an IPv6 address at a position.
+    *
+    * Return the end of the address, None if there is no address
+    */
+    fn match_ipv6(&self, pos : uint) -> Option<uint> {
+        let mut end = pos;
+        let mut compressed = None;
+        let mut digit = false;
+        let mut colons = 0;
+
+        if pos > 0 {
+            let previous = self.datas[pos - 1];
+            if !self.is_delimiter(previous)
+                && (!self.is_special_char(previous) || previous == ':' || previous == '.') {
+                return None;
+            }
+        }
+        while end < self.datas.len()
+            && (self.datas[end] == ':' || self.datas[end].to_digit(16).is_some()) {
+            if self.datas[end].is_digit() {
+                digit = true;
+            }
+            if self.datas[end] == ':' {
+                colons += 1;
+            }
+            if compressed.is_none() && end > pos
+                && self.datas[end] == ':' && self.datas[end - 1] == ':' {
+                compressed = Some(end - 1);
+            }
+            end += 1;
+        }
+        if !digit || !self.is_dotted_end(end) {
+            return None;
+        }
+        let groups = match compressed {
+            Some(split) => {
+                match (hex_groups(self.datas.slice(pos, split)),
+                       hex_groups(self.datas.slice(split + 2, end))) {
+                    (Some(left), Some(right)) if left + right < 8
+                        && (split == pos || colons >= 3)            => Some(8),
+                    _                                               => None
+                }
+            }
+            None        => hex_groups(self.datas.slice(pos, end))
+        };
+        if groups == Some(8) { Some(end) } else { None }
+    }
+
+    /**
+    * Private function, match a semantic version at a position.
+    *
+    * Return the end of the version, None if there is no version
+    */
+    fn match_version(&self, pos : uint) -> Option<uint> {
+        let mut i = pos;
+
+        for uint::range(0, 3) |group| {
+            let end = self.digits_end(i);
+            if end == i || (end - i > 1 && self.datas[i] == '0') {
+                return None;
+            }
+            i = end;
+            if group < 2 {
+                if i >= self.datas.len() || self.datas[i] != '.' {
+                    return None;
+                }
+                i += 1;
+            }
+        }
+        for ['-', '+'].iter().advance |separator| {
+            if i < self.datas.len() && self.datas[i] == *separator {
+                match self.version_identifiers_end(i + 1) {
+                    Some(end)   => i = end,
+                    None        => {}
+                }
+            }
+        }
+        if self.is_dotted_end(i) { Some(i) } else { None }
+    }
+
+    /**
+    * Private function, find the end of the pre-release or build identifiers
+    * of a version ( rc.1 / build-5 ).
+    *
+    * Return the end of the identifiers, None if there is no identifier
+    */
+    fn version_identifiers_end(&self, pos : uint) -> Option<uint> {
+        let mut i = pos;
+
+        while i < self.datas.len() {
+            let begin = i;
+            while i < self.datas.len() && is_label_char(self.datas[i]) {
+                i += 1;
+            }
+            if i == begin {
+                return None;
+            }
+            if i + 1 < self.datas.len() && self.datas[i] == '.' && is_label_char(self.datas[i + 1]) {
+                i += 1;
+            }
+            else {
+                break;
+            }
+        }
+        if i == pos { None } else { Some(i) }
+    }
+
+    /**
+    * Private function, match a number with at least two dots at a position.
+    *
+    * Return the end of the number, None if there is no dotted number
+    */
+    fn match_dotted_number(&self, pos : uint) -> Option<uint> {
+        let mut i = self.digits_end(pos);
+        let mut groups = 1;
+
+        while i + 1 < self.datas.len() && self.datas[i] == '.' && self.datas[i + 1].is_digit() {
+            i = self.digits_end(i + 1);
+            groups += 1;
+        }
+        if groups >= 3 && self.is_dotted_end(i) { Some(i) } else { None }
+    }
+
+    /**
+    * Private function, check if the found word is a number or a number with a unit.
+    *
+    * The unit is in the word ( 10px ) or directly after the word ( 50% ).
+    *
+    * Return true if the token is a Number or a Dimension, false otherwise
+    */
+    fn get_number_token(&mut self) -> bool {
+        let word = self.word.clone();
+        let mut found = false;
+
+        if self.is_number() {
+            self.token = Number;
+            self.number = self.word.clone();
+            match self.find_unit_at(self.pos) {
+                Some(unit)  => {
+                    self.pos += unit.char_len();
+                    self.word = self.word + unit;
+                    self.unit = unit;
+                    self.token = Dimension;
+                }
+                None        => {}
+            }
+            return true;
+        }
+        for self.units.clone().iter().advance |unit| {
+            if word.len() > unit.len() && word.ends_with(*unit)
+                && (!found || unit.len() > self.unit.len()) {
+                self.word = word.slice(0, word.len() - unit.len()).to_owned();
+                if self.is_number() {
+                    self.number = self.word.clone();
+                    self.unit = unit.clone();
+                    self.token = Dimension;
+                    found = true;
+                }
+            }
+        }
+        self.word = word;
+        return found;
+    }
+
+    /**
+    * Private function, find the longest unit at a position, the unit must end the word.
+    *
+    * Return the unit, None if there is no unit
+    */
+    fn find_unit_at(&self, pos : uint) -> Option<~str> {
+        let mut found : Option<~str> = None;
+
+        for self.units.iter().advance |unit| {
+            let end = pos + unit.char_len();
+            let longer = match found {
+                Some(ref longest)   => unit.char_len() > longest.char_len(),
+                None                => true
+            };
+            if longer && end <= self.datas.len()
+                && str::from_chars(self.datas.slice(pos, end)) == *unit
+                && (end == self.datas.len() || self.is_delimiter(self.datas[end])
+                    || self.is_special_char(self.datas[end]) || self.line_break_len(end) > 0) {
+                found = Some(unit.clone());
+            }
+        }
+        found
+    }
+
+    /**
+    * Private function, check if the found word is a number
+    *
+    * Return true if it's a number, false otherwise
+    */
+    fn is_number(&mut self) -> bool {
+        let chars = str_to_vec(self.word.clone());
+        let mut dot = 0;
+        let mut digits = 0;
+        let mut exponent = false;
+        let mut i = 0;
+
+        if self.signedNumbers && (self.word == ~"Infinity" || self.word == ~"NaN") {
+            return true;
+        }
+        if self.hexNumbers && chars.len() > 2
+            && chars[0] == '0' && (chars[1] == 'x' || chars[1] == 'X') {
+            for chars.slice(2, chars.len()).iter().advance |schar| {
+                if !self.is_digit_separator(*schar) && schar.to_digit(16).is_none() {
+                    return false;
+                }
+            }
+            return true;
+        }
+        while i < chars.len() {
+            let schar = chars[i];
+            if schar == '.' && !exponent {
+                dot += 1;
+            }
+            else if self.exponentNumbers && !exponent && digits > 0
+                && (schar == 'e' || schar == 'E') {
+                exponent = true;
+                if i + 1 < chars.len() && (chars[i + 1] == '-' || chars[i + 1] == '+') {
+                    i += 1;
+                }
+                if i + 1 == chars.len() {
+                    return false;
+                }
+            }
+            else if schar.is_digit() {
+                digits += 1;
+            }
+            else if !(digits > 0 && self.is_digit_separator(schar)) {
+                return false;
+            }
+            i += 1;
+        }
+        if dot > 1 || digits == 0 {
+            return false;
+        }
+        return true;
+    }
+
+    /**
+    * Private function, test if a char is the digit separator
+    *
+    * Return true if the char is the digit separator, false otherwise
+    */
+    fn is_digit_separator(&self, testChar : char) -> bool {
+        match self.digitSeparator {
+            Some(separator) => separator == testChar,
+            None            => false
+        }
+    }
+
+
+    /**
+    * Private function, check if the found word is a boolean or a null literal
+    *
+    * Return true if it is a literal, the token is a Bool or a Null, false otherwise
+    */
+    fn is_literal(&mut self) -> bool {
+        let word = if self.caseInsensitiveKeywords { fold_str(self.word) } else { self.word.clone() };
+        let mut found = None;
+
+        for self.literals.iter().advance |&(ref literal, value)| {
+            let spelling = if self.caseInsensitiveKeywords { fold_str(*literal) } else { literal.clone() };
+            if found.is_none() && spelling == word {
+                found = Some(value);
+            }
+        }
+        match found {
+            Some(Some(value))   => { self.boolean = value; self.token = Bool; true }
+            Some(None)          => { self.token = Null; true }
+            None                => false
+        }
+    }
+
+    /**
+    * Private function, ask the predicate if the found soft keyword is a keyword.
+    *
+    * Return true if it is a keyword, false otherwise
+    */
+    fn is_soft_keyword(&self) -> bool {
+        match self.softKeywordPredicate {
+            Some(predicate) => predicate(self.word, self.history),
+            None            => false
+        }
+    }
+
+    /**
+    * Private functions, check if the found word is a keyword
+    *
+    * Return the index of the keyword, None if the word is not a keyword
+    */
+    fn find_keyword(&mut self) -> Option<uint> {
+        self.scan(self.tokenBegin);
+        let found = self.lexicon.ending_at(self.pos, |pattern| match pattern {
+            FixedKeyword(_) => true,
+            _               => false
+        });
+
+        if found.is_empty() {
+            return None;
+        }
+        match found[0] {
+            FixedKeyword(index) => Some(index),
+            _                   => None
+        }
+    }
+
+    /**
+    * Private function, read the delimiters and the comments following the
+    * current token on the same line.
+    */
+    fn consume_trailing_trivia(&mut self) -> () {
+        let mut found = true;
+
+        while found {
+            while self.pos < self.datas.len() && self.line_break_len(self.pos) == 0
+                && self.is_delimiter(self.datas[self.pos]) {
+                self.pos += 1;
+            }
+            found = self.pos < self.datas.len() && self.line_break_len(self.pos) == 0
+                && self.has_comments();
+        }
+    }
+
+    /**
+    * Private function, attach the delimiters and the comments around the
+    * current token to it.
+    *
+    * A Newline has no trailing trivia, the indentation of the next line is
+    * the leading trivia of the next token.
+    */
+    fn attach_trivia(&mut self) -> () {
+        self.leadingTrivia = str::from_chars(self.source.slice(self.triviaEnd, self.span.begin));
+        if !self.in_interpolated_string() && self.token != Newline {
+            self.consume_trailing_trivia();
+        }
+        self.triviaEnd = self.physical_pos(self.pos);
+        self.trailingTrivia = str::from_chars(self.source.slice(self.span.end, self.triviaEnd));
+    }
+
+    /**
+    * Private function, provide the EndOfFile token in lossless mode.
+    *
+    * Return true if the token is an EndOfFile, false otherwise
+    */
+    fn get_end_of_file(&mut self) -> bool {
+        if !self.lossless || self.endOfDatas {
+            return false;
+        }
+        self.endOfDatas = true;
+        self.leadingTrivia = str::from_chars(self.source.slice(self.triviaEnd, self.source.len()));
+        self.trailingTrivia = ~"";
+        self.triviaEnd = self.source.len();
+        self.span = Span { begin : self.source.len(), end : self.source.len() };
+        self.mode = self.modes[self.currentMode].name.clone();
+        self.token = EndOfFile;
+        return true;
+    }
+
+    /**
+    * Get the text of the current token with its trivia, as written in the datas.
+    *
+    * In lossless mode the concatenation of the full texts of all the tokens
+    * is the datas.
+    *
+    * Return the leading trivia, the text and the trailing trivia of the token.
+    */
+    pub fn get_full_text(&self) -> ~str {
+        self.leadingTrivia + str::from_chars(self.source.slice(self.span.begin, self.span.end))
+            + self.trailingTrivia
+    }
+
+    /**
+    * Define the brackets who open and close the groups of token_trees.
+    *
+    * The brackets are added to the special chars.
+    *
+    * # Default
+    * * By default the brackets are ( and ), [ and ], { and }
+    *
+    * # Arguments
+    * * pairs - The opening and the closing chars of each bracket
+    */
+    pub fn set_bracket_pairs(&mut self, pairs : &[(char, char)]) -> () {
+        self.bracketPairs.clear();
+        for pairs.iter().advance |&(open, close)| {
+            self.add_specialchar(open);
+            self.add_specialchar(close);
+            self.bracketPairs.push((open, close));
+        }
+    }
+
+    /**
+    * Read all the tokens and nest the tokens between brackets in groups.
+    *
+    * # Example
+    * * This string |f(a, [b])| provide these trees |f - ( a - , - [ b ] )|
+    *
+    * Return the trees, an error with the spans of the brackets if a bracket
+    * is not closed, is closed by another bracket or close nothing.
+    */
+    pub fn token_trees(&mut self) -> Result<~[TokenTree], BracketError> {
+        let mut groups : ~[TokenGroup] = ~[];
+        let mut trees : ~[TokenTree] = ~[];
+
+        while self.has_token() {
+            let bracket = match self.token {
+                SpecialChar => self.bracket(self.specialChar),
+                _           => None
+            };
+            match bracket {
+                Some((open, _)) if open == self.specialChar => {
+                    groups.push(TokenGroup {
+                        delimiter : open,
+                        openSpan : self.span,
+                        closeSpan : self.span,
+                        children : ~[]
+                    });
+                }
+                Some((open, _))                             => {
+                    let mut group = match groups.pop_opt() {
+                        Some(group) => group,
+                        None        => return Err(BracketError {
+                            message : fmt!("unmatched closing bracket '%c'", self.specialChar),
+                            openSpan : None,
+                            closeSpan : Some(self.span)
+                        })
+                    };
+                    if group.delimiter != open {
+                        return Err(BracketError {
+                            message : fmt!("bracket '%c' closed by '%c'", group.delimiter,
+                                           self.specialChar),
+                            openSpan : Some(group.openSpan),
+                            closeSpan : Some(self.span)
+                        });
+                    }
+                    group.closeSpan = self.span;
+                    StringTokenizer::add_tree(&mut groups, &mut trees, Group(group));
+                }
+                None                                        => {
+                    let token = Leaf(self.get_token_data());
+                    StringTokenizer::add_tree(&mut groups, &mut trees, token);
+                }
+            }
+        }
+        match groups.pop_opt() {
+            Some(group) => Err(BracketError {
+                message : fmt!("unmatched opening bracket '%c'", group.delimiter),
+                openSpan : Some(group.openSpan),
+                closeSpan : None
+            }),
+            None        => Ok(trees)
+        }
+    }
+
+    /**
+    * Private function, add a tree to the innermost opened group, or to the
+    * trees if there is no opened group.
+    */
+    fn add_tree(groups : &mut ~[TokenGroup], trees : &mut ~[TokenTree], tree : TokenTree) -> () {
+        let len = groups.len();
+
+        if len > 0 {
+            groups[len - 1].children.push(tree);
+        }
+        else {
+            trees.push(tree);
+        }
+    }
+
+    /**
+    * Private function, find the bracket of a char.
+    *
+    * Return the opening and the closing chars of the bracket, None if the char
+    * is not a bracket.
+    */
+    fn bracket(&self, testChar : char) -> Option<(char, char)> {
+        for self.bracketPairs.iter().advance |&(open, close)| {
+            if testChar == open || testChar == close {
+                return Some((open, close));
+            }
+        }
+        None
+    }
+
+    /**
+    * Get a copy of the current token.
+    *
+    * Return the TokenData of the current token.
+    */
+    pub fn get_token_data(&self) -> TokenData {
+        TokenData {
+            token : self.token,
+            text : self.get_token_text(),
+            leadingTrivia : self.leadingTrivia.clone(),
+            trailingTrivia : self.trailingTrivia.clone(),
+            span : self.span,
+            mode : self.mode.clone()
+        }
+    }
+
+    /**
+    * Print tokens with the minimal spacing.
+    *
+    * A delimiter is inserted between two tokens only if their texts would be read
+    * as other tokens with the settings of this StringTokenizer. The Newline tokens
+    * are kept and the lines are indented with four spaces by Indent token.
+    *
+    * # Arguments
+    * * tokens - The tokens to print
+    *
+    * Return the text of the tokens.
+    */
+    pub fn print_tokens(&self, tokens : &[TokenData]) -> ~str {
+        self.print(tokens, false, "    ")
+    }
+
+    /**
+    * Print tokens with their trivia.
+    *
+    * The trivia of tokens read in lossless mode are kept, the tokens without
+    * trivia are separated like with print_tokens. The line continuations are
+    * not in the trivia and are not printed.
+    *
+    * # Arguments
+    * * tokens - The tokens to print
+    *
+    * Return the text of the tokens.
+    */
+    pub fn print_tokens_with_trivia(&self, tokens : &[TokenData]) -> ~str {
+        self.print(tokens, true, "")
+    }
+
+    /**
+    * Minify the datas.
+    *
+    * The comments, the blank lines and the delimiters who are not needed to
+    * separate two tokens are removed, the strings are kept. The Newline tokens
+    * and the indentation of the offside rule are kept with one space by level.
+    * When the line breaks are not tokens, the lines beginning with a '#' special
+    * char ( C preprocessor directives ) keep the line break who end them.
+    * The datas are read from the current position with the current settings,
+    * use set_profile to minify a language ( JsonProfile / CssProfile / CProfile ).
+    *
+    * Return the minified datas.
+    */
+    pub fn minify(&mut self) -> ~str {
+        let mut tokens : ~[TokenData] = ~[];
+        let mut lineBegin = true;
+        let mut directive = false;
+        let mut lastEnd = self.pos;
+
+        while self.has_token() {
+            let lineStart = tokens.is_empty() || self.has_line_break(lastEnd, self.tokenBegin);
+            if directive && lineStart {
+                tokens.push(TokenData {
+                    token : Newline,
+                    text : ~"\n",
+                    leadingTrivia : ~"",
+                    trailingTrivia : ~"",
+                    span : Span { begin : self.span.begin, end : self.span.begin },
+                    mode : self.mode.clone()
+                });
+                directive = false;
+            }
+            if !self.returnIsToken && lineStart && self.token == SpecialChar
+                && self.specialChar == '#' {
+                directive = true;
+            }
+            match self.token {
+                Newline if lineBegin    => {}
+                _                       => {
+                    lineBegin = self.token == Newline;
+                    tokens.push(self.get_token_data());
+                }
+            }
+            lastEnd = self.pos;
+        }
+        self.print(tokens, false, " ")
+    }
+
+    /**
+    * Private function, test if there is a line break in the datas between two positions.
+    *
+    * Return true if a char of the range is a line break, false otherwise
+    */
+    fn has_line_break(&self, begin : uint, end : uint) -> bool {
+        let mut pos = begin;
+
+        while pos < end && pos < self.datas.len() {
+            if self.is_line_break(self.datas[pos]) {
+                return true;
+            }
+            pos += 1;
+        }
+        return false;
+    }
+
+    /**
+    * Private function, print tokens.
+    *
+    * # Arguments
+    * * tokens - The tokens to print
+    * * trivia - true if the trivia of the tokens are printed, false otherwise
+    * * indentation - The indentation printed by Indent token without trivia
+    *
+    * Return the text of the tokens.
+    */
+    fn print(&self, tokens : &[TokenData], trivia : bool, indentation : &str) -> ~str {
+        let mut text = ~"";
+        let mut previous = None;
+        let mut depth = 0u;
+        let mut lineBegin = true;
+        let mut scratch = self.clone();
+        let mut i = 0;
+
+        scratch.set_lossless(false);
+        while i < tokens.len() {
+            let token = &tokens[i];
+            match token.token {
+                Indent  => depth += 1,
+                Dedent  => if depth > 0 { depth -= 1 },
+                _       => {
+                    let merge = match previous {
+                        Some(last) if !trivia || (tokens[last].trailingTrivia.len() == 0
+                                                  && token.leadingTrivia.len() == 0)
+                                    => scratch.tokens_merge(&tokens[last], token),
+                        _           => false
+                    };
+                    if merge {
+                        text.push_char(self.separator());
+                    }
+                    if trivia {
+                        text.push_str(token.leadingTrivia);
+                    }
+                    else if lineBegin && token.text.len() > 0 && token.token != Newline {
+                        for depth.times {
+                            text.push_str(indentation);
+                        }
+                    }
+                    text.push_str(token.text);
+                    if trivia {
+                        text.push_str(token.trailingTrivia);
+                    }
+                    if token.text.len() > 0 {
+                        lineBegin = token.token == Newline;
+                    }
+                    previous = Some(i);
+                }
+            }
+            i += 1;
+        }
+        text
+    }
+
+    /**
+    * Private function, test if two tokens printed without delimiter are read
+    * as other tokens.
+    *
+    * It is called on a copy of the printing StringTokenizer, the lookup tables
+    * of the modes are compiled by the first call and kept for the next ones.
+    * The texts are read in the mode of the first token.
+    *
+    * Return true if a delimiter is needed between the tokens, false otherwise
+    */
+    fn tokens_merge(&mut self, first : &TokenData, second : &TokenData) -> bool {
+        if first.text.len() == 0 || second.text.len() == 0
+            || !StringTokenizer::is_separable(first.token)
+            || !StringTokenizer::is_separable(second.token) {
+            return false;
+        }
+        self.set_datas(str_to_vec(first.text + second.text));
+        match self.find_mode(first.mode) {
+            Some(mode)  => self.switch_mode(mode),
+            None        => {}
+        }
+        if !self.has_token() || self.token != first.token || self.get_token_text() != first.text {
+            return true;
+        }
+        if !self.has_token() || self.token != second.token
+            || self.get_token_text() != second.text {
+            return true;
+        }
+        self.has_token()
+    }
+
+    /**
+    * Private function, test if a delimiter can be printed before or after a token.
+    *
+    * Return false for the line breaks and the parts of the interpolated strings
+    */
+    fn is_separable(token : Token) -> bool {
+        match token {
+            StrStart | StrFragment | InterpStart | InterpEnd | StrEnd | Newline  => false,
+            _                                                                   => true
+        }
+    }
+
+    /**
+    * Private function, find the delimiter printed between two tokens.
+    *
+    * Return ' ' if it is a delimiter, the first delimiter otherwise
+    */
+    fn separator(&self) -> char {
+        for self.delimiters.iter().advance |delimiter| {
+            if *delimiter == ' ' {
+                return ' ';
+            }
+        }
+        for self.delimiters.iter().advance |delimiter| {
+            if !self.is_line_break(*delimiter) {
+                return *delimiter;
+            }
+        }
+        ' '
+    }
+
+    /**
+    * Try to find new tokens.
+    *
+    * Return true if there is token, false otherwise.
+    */
+    pub fn has_token(&mut self) -> bool {
+        if !self.next_token() {
+            return self.get_end_of_file();
+        }
+        self.span.begin = self.physical_pos(self.tokenBegin);
+        self.span.end = if self.pos > self.tokenBegin {
+            self.physical_pos(self.pos - 1) + 1
+        }
+        else {
+            self.span.begin
+        };
+        if self.lossless {
+            self.attach_trivia();
+        }
+        self.track_interpolation_depth();
+        self.track_bracket_depth();
+        if self.softKeywordPredicate.is_some() {
+            self.history.push(self.get_token_data());
+            if self.history.len() > SOFT_KEYWORD_HISTORY {
+                self.history.shift();
+            }
+        }
+        self.lastToken = self.token;
+        self.lastSpecialChar = self.specialChar;
+        self.lastTokenEnd = self.pos;
+        self.mode = self.modes[self.currentMode].name.clone();
+        self.apply_mode_transition();
+        return true;
+    }
+
+    /**
+    * Private function, read the next token with the current mode.
+    *
+    * Return true if there is token, false otherwise.
+    */
+    fn next_token(&mut self) -> bool {
+        self.tokenBegin = self.pos;
+        self.maybeKeyword = false;
+        if self.get_dedent(false) {
+            return true;
+        }
+        if self.pos == self.datas.len() {
+            return self.get_dedent(true);
+        }
+        else {
+            self.compile();
+            if self.in_interpolated_string() {
+                return self.get_string_part();
+            }
+            let found = self.clean_for_next_token();
+            self.tokenBegin = self.pos;
+            if !found {
+                return self.get_dedent(true);
+            } 
+            if self.offsideRule && self.get_indentation_token() {
+                return true;
+            }
+            else if self.returnIsToken && self.get_newline() {
+                return true;
+            }
+            else if self.get_interpolation_token() {
+                return true;
+            }
+            else if self.get_location_token() {
+                return true;
+            }
+            else if self.dateTimes && self.get_date_time_token() {
+                return true;
+            }
+            else if self.get_dotted_token() {
+                return true;
+            }
+            else if self.signedNumbers && self.get_signed_number() {
+                return true;
+            }
+            else if self.get_sigiled_token() {
+                return true;
+            }
+            else {
+                let begin = self.pos;
+                self.get_builtin_token();
+                if self.get_custom_token(begin) {
+                    self.maybeKeyword = false;
+                    self.token = Custom;
+                }
+                return true;
+            }
+        }
+    }
+
+    /**
+    * Private function, read an operator, a special char or a word who can be
+    * a number, a literal or a keyword.
+    */
+    fn get_builtin_token(&mut self) -> () {
+        if self.get_operator() {
+            self.token = Operator;
+        }
+        else if self.is_special_char(self.datas[self.pos]) && !self.begin_identifier() {
+            self.specialChar = self.datas[self.pos];
+            self.token = SpecialChar;
+            self.pos += 1;
+        }
+        else {
+            let tstr = self.get_word();
+            self.word = tstr;
+            if self.get_number_token() {
+                return;
+            }
+            else if self.is_literal() {
+                self.literal = self.word.clone();
+            }
+            else {
+                match self.find_keyword() {
+                    Some(index) => {
+                        self.token = KeyWord;
+                        let (id, category) = self.keywordKinds[index];
+                        self.keyword = self.keyWords[index].clone();
+                        self.keywordId = id;
+                        self.keywordCategory = category;
+                        if category == ContextualKeyword && !self.is_soft_keyword() {
+                            self.maybeKeyword = true;
+                            self.token = Word;
+                        }
+                    }
+                    None        => self.token = Word
+                }
+            }
+        }
+    }
+}
+
+/**
+* Implementation of trait Clone, clone a StringTokenizer instance
+*/
+impl Clone for StringTokenizer {
+    fn clone(&self) -> StringTokenizer {
+        StringTokenizer {
+            datas : self.datas.clone(),
+            source : self.source.clone(),
+            physical : self.physical.clone(),
+            lineContinuation : self.lineContinuation.clone(),
+            lossless : self.lossless,
+            triviaEnd : self.triviaEnd,
+            endOfDatas : self.endOfDatas,
+            pos : self.pos,
+            tokenBegin : self.tokenBegin,
+            keyWords : self.keyWords.clone(),
+            keywordKinds : self.keywordKinds.clone(),
+            literals : self.literals.clone(),
+            caseInsensitiveKeywords : self.caseInsensitiveKeywords,
+            delimiters : self.delimiters.clone(),
+            specialChars : self.specialChars.clone(),
+            operators : self.operators.clone(),
+            quoteChars : self.quoteChars.clone(),
+            hexNumbers : self.hexNumbers,
+            exponentNumbers : self.exponentNumbers,
+            signedNumbers : self.signedNumbers,
+            versions : self.versions,
+            ipAddresses : self.ipAddresses,
+            dottedNumbers : self.dottedNumbers,
+            dateTimes : self.dateTimes,
+            urls : self.urls,
+            emails : self.emails,
+            paths : self.paths,
+            digitSeparator : self.digitSeparator,
+            units : self.units.clone(),
+            sigils : self.sigils.clone(),
+            numberedSigils : self.numberedSigils.clone(),
+            identifierChars : self.identifierChars.clone(),
+            rules : self.rules.clone(),
+            interpolatedStrings : self.interpolatedStrings.clone(),
+            offsideRule : self.offsideRule,
+            comments : self.comments,
+            returnIsToken : self.returnIsToken,
+            unicodeNewLines : self.unicodeNewLines,
+            collapseBlankLines : self.collapseBlankLines,
+            ignoreEscapeChar : self.ignoreEscapeChar,
+            stringPrefixes : self.stringPrefixes.clone(),
+            tripleQuotes : self.tripleQuotes,
+            doubledQuotes : self.doubledQuotes,
+            nestedComments : self.nestedComments,
+            multiCommentBegin : self.multiCommentBegin.clone(),
+            multiCommentEnd : self.multiCommentEnd.clone(),
+            singleComment : self.singleComment.clone(),
+            compiled : false,
+            charTable : CharTable::new(),
+            lexicon : Lexicon::new(),
+            modes : self.modes.clone(),
+            currentMode : self.currentMode,
+            modeStack : self.modeStack.clone(),
+            interpolations : self.interpolations.clone(),
+            bracketPairs : self.bracketPairs.clone(),
+            softKeywordPredicate : self.softKeywordPredicate,
+            history : self.history.clone(),
+            indentStack : self.indentStack.clone(),
+            pendingDedents : self.pendingDedents,
+            indentLine : self.indentLine,
+            bracketDepth : self.bracketDepth,
+            lastToken : self.lastToken,
+            lastSpecialChar : self.lastSpecialChar,
+            lastTokenEnd : self.lastTokenEnd,
+            token : self.token,
+            number : self.number.clone(),
+            unit : self.unit.clone(),
+            dotted : self.dotted.clone(),
+            dateTime : self.dateTime.clone(),
+            timestamp : self.timestamp.clone(),
+            duration : self.duration,
+            location : self.location.clone(),
+            sigil : self.sigil,
+            name : self.name.clone(),
             word : self.word.clone(),
             keyword : self.keyword.clone(),
-            specialChar : self.specialChar
+            keywordId : self.keywordId,
+            keywordCategory : self.keywordCategory,
+            maybeKeyword : self.maybeKeyword,
+            literal : self.literal.clone(),
+            boolean : self.boolean,
+            specialChar : self.specialChar,
+            operator : self.operator.clone(),
+            custom : self.custom.clone(),
+            customKind : self.customKind.clone(),
+            mode : self.mode.clone(),
+            stringPart : self.stringPart.clone(),
+            indentation : self.indentation.clone(),
+            indentError : self.indentError.clone(),
+            newline : self.newline.clone(),
+            span : self.span,
+            leadingTrivia : self.leadingTrivia.clone(),
+            trailingTrivia : self.trailingTrivia.clone()
+        }
+    }
+}
+
+/**
+* Implementation of trait Clone, clone the dictionnary of a lexer mode
+*
+* The lookup tables are compiled again when the mode is used.
+*/
+impl Clone for ModeSettings {
+    fn clone(&self) -> ModeSettings {
+        ModeSettings {
+            keyWords : self.keyWords.clone(),
+            keywordKinds : self.keywordKinds.clone(),
+            literals : self.literals.clone(),
+            caseInsensitiveKeywords : self.caseInsensitiveKeywords,
+            delimiters : self.delimiters.clone(),
+            specialChars : self.specialChars.clone(),
+            operators : self.operators.clone(),
+            quoteChars : self.quoteChars.clone(),
+            hexNumbers : self.hexNumbers,
+            exponentNumbers : self.exponentNumbers,
+            signedNumbers : self.signedNumbers,
+            versions : self.versions,
+            ipAddresses : self.ipAddresses,
+            dottedNumbers : self.dottedNumbers,
+            dateTimes : self.dateTimes,
+            urls : self.urls,
+            emails : self.emails,
+            paths : self.paths,
+            digitSeparator : self.digitSeparator,
+            units : self.units.clone(),
+            sigils : self.sigils.clone(),
+            numberedSigils : self.numberedSigils.clone(),
+            identifierChars : self.identifierChars.clone(),
+            rules : self.rules.clone(),
+            interpolatedStrings : self.interpolatedStrings.clone(),
+            offsideRule : self.offsideRule,
+            comments : self.comments,
+            returnIsToken : self.returnIsToken,
+            unicodeNewLines : self.unicodeNewLines,
+            collapseBlankLines : self.collapseBlankLines,
+            ignoreEscapeChar : self.ignoreEscapeChar,
+            stringPrefixes : self.stringPrefixes.clone(),
+            tripleQuotes : self.tripleQuotes,
+            doubledQuotes : self.doubledQuotes,
+            nestedComments : self.nestedComments,
+            multiCommentBegin : self.multiCommentBegin.clone(),
+            multiCommentEnd : self.multiCommentEnd.clone(),
+            singleComment : self.singleComment.clone(),
+            compiled : false,
+            charTable : CharTable::new(),
+            lexicon : Lexicon::new()
+        }
+    }
+}
+
+#[test]
+fn test_with_strotkenierrs() -> () {
+    let path = ~PosixPath("./strtokenizer.rs");
+    let file = match io::read_whole_file_str(path) {
+        Ok(file)        => file,
+        Err(error)      => fail!(fmt!("Error during file reading :\n%s", error))
+    };
+    let i = 42;
+    let j = 42.42;
+    let h = 2345678;
+    //io::println(fmt!("File main.rs :\n\n%s\nTokens :\n\n", file));
+    let mut st = StringTokenizer::new_with_str(file);
+    st.set_comments(AllComments);
+    st.set_new_line_as_token(false);
+    st.add_keyword(~"let");
+    while st.has_token() {
+        match st.token {
+            SpecialChar   => io::println(fmt!("SPECIAL CHAR : %c", st.specialChar)),
+            Word          => io::println(fmt!("WORD : %s", st.word)),
+            Number        => io::println(fmt!("NUMBER : %s", st.number)),
+            KeyWord       => io::println(fmt!("KEYWORD : %s", st.keyword)),
+            Operator      => io::println(fmt!("OPERATOR : %s", st.operator)),
+            Custom        => io::println(fmt!("%s : %s", st.customKind, st.custom)),
+            StrStart | StrFragment | InterpStart | InterpEnd | StrEnd
+                          => io::println(fmt!("STRING PART : %s", st.stringPart)),
+            Indent        => io::println("INDENT"),
+            Dedent        => io::println("DEDENT"),
+            IndentError   => io::println(fmt!("INDENT ERROR : %s", st.indentError)),
+            Newline       => io::println("NEW LINE"),
+            EndOfFile     => io::println("END OF FILE"),
+            Bool          => io::println(fmt!("BOOL : %b", st.boolean)),
+            Null          => io::println(fmt!("NULL : %s", st.literal)),
+            Dimension     => io::println(fmt!("DIMENSION : %s %s", st.number, st.unit)),
+            Version       => io::println(fmt!("VERSION : %s", st.dotted)),
+            IpAddress     => io::println(fmt!("IP ADDRESS : %s", st.dotted)),
+            DottedNumber  => io::println(fmt!("DOTTED NUMBER : %s", st.dotted)),
+            Date          => io::println(fmt!("DATE : %s", st.dateTime)),
+            Time          => io::println(fmt!("TIME : %s", st.dateTime)),
+            DateTime      => io::println(fmt!("DATE TIME : %s", st.dateTime)),
+            Duration      => io::println(fmt!("DURATION : %s ns", st.duration.to_str())),
+            Url           => io::println(fmt!("URL : %s", st.location)),
+            Email         => io::println(fmt!("EMAIL : %s", st.location)),
+            Path          => io::println(fmt!("PATH : %s", st.location)),
+            Sigiled       => io::println(fmt!("SIGILED : %c %s", st.sigil, st.name)),
+            NoToken       => {}
+        }
+    }
+}
+
+#[cfg(test)]
+fn dump_tokens(st : &mut StringTokenizer) -> ~[~str] {
+    let mut tokens = ~[];
+
+    while st.has_token() {
+        match st.token {
+            SpecialChar   => tokens.push(fmt!("S(%c)", st.specialChar)),
+            Word          => tokens.push(fmt!("W(%s)", st.word)),
+            Number        => tokens.push(fmt!("N(%s)", st.number)),
+            KeyWord       => tokens.push(fmt!("K(%s)", st.keyword)),
+            Operator      => tokens.push(fmt!("O(%s)", st.operator)),
+            Custom        => tokens.push(fmt!("C(%s:%s)", st.customKind, st.custom)),
+            StrStart      => tokens.push(fmt!("SS(%s)", st.stringPart)),
+            StrFragment   => tokens.push(fmt!("SF(%s)", st.stringPart)),
+            InterpStart   => tokens.push(fmt!("IS(%s)", st.stringPart)),
+            InterpEnd     => tokens.push(fmt!("IE(%s)", st.stringPart)),
+            StrEnd        => tokens.push(fmt!("SE(%s)", st.stringPart)),
+            Indent        => tokens.push(~"INDENT"),
+            Dedent        => tokens.push(~"DEDENT"),
+            IndentError   => tokens.push(fmt!("E(%s)", st.indentError)),
+            Newline       => tokens.push(~"NL"),
+            EndOfFile     => tokens.push(~"EOF"),
+            Bool          => tokens.push(fmt!("B(%s:%b)", st.literal, st.boolean)),
+            Null          => tokens.push(fmt!("NU(%s)", st.literal)),
+            Dimension     => tokens.push(fmt!("D(%s:%s)", st.number, st.unit)),
+            Version       => tokens.push(fmt!("V(%s)", st.dotted)),
+            IpAddress     => tokens.push(fmt!("IP(%s)", st.dotted)),
+            DottedNumber  => tokens.push(fmt!("DN(%s)", st.dotted)),
+            Date | Time | DateTime
+                          => tokens.push(fmt!("DT(%s:%s)", st.dateTime, dump_timestamp(&st.timestamp))),
+            Duration      => tokens.push(fmt!("DU(%s:%s)", st.dateTime, st.duration.to_str())),
+            Url           => tokens.push(fmt!("U(%s)", st.location)),
+            Email         => tokens.push(fmt!("M(%s)", st.location)),
+            Path          => tokens.push(fmt!("P(%s)", st.location)),
+            Sigiled       => tokens.push(fmt!("SG(%c:%s)", st.sigil, st.name)),
+            NoToken       => {}
+        }
+    }
+    tokens
+}
+
+#[test]
+fn test_c_profile() -> () {
+    let mut st = StringTokenizer::new_with_profile(
+        ~"int x = 0x1F; /* c */ x += 1.5e-3; // end\nif (x >= 10 && y) return p->q;",
+        CProfile);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"K(int)", ~"W(x)", ~"S(=)", ~"N(0x1F)", ~"S(;)", ~"W(x)", ~"O(+=)",
+                 ~"N(1.5e-3)", ~"S(;)", ~"K(if)", ~"S(()", ~"W(x)", ~"O(>=)", ~"N(10)",
+                 ~"O(&&)", ~"W(y)", ~"S())", ~"K(return)", ~"W(p)", ~"O(->)", ~"W(q)",
+                 ~"S(;)"]);
+
+    let mut corpus = StringTokenizer::new_with_profile(
+        ~"#include <stdio.h>\n/* multi\n   line */\nchar *s = \"a \\\"b\\\"\";\nchar c = '\\'';\n#define MAX(a, b) \\\n    ((a) > (b))\n",
+        CProfile);
+    assert_eq!(dump_tokens(&mut corpus),
+               ~[~"S(#)", ~"W(include)", ~"S(<)", ~"W(stdio)", ~"S(.)", ~"W(h)", ~"S(>)",
+                 ~"K(char)", ~"S(*)", ~"W(s)", ~"S(=)", ~"W(\"a \\\"b\\\"\")", ~"S(;)",
+                 ~"K(char)", ~"W(c)", ~"S(=)", ~"W('\\'')", ~"S(;)", ~"S(#)", ~"W(define)",
+                 ~"W(MAX)", ~"S(()", ~"W(a)", ~"S(,)", ~"W(b)", ~"S())", ~"S(()", ~"S(()",
+                 ~"W(a)", ~"S())", ~"S(>)", ~"S(()", ~"W(b)", ~"S())", ~"S())"]);
+}
+
+#[test]
+fn test_rust_profile() -> () {
+    let mut st = StringTokenizer::new_with_profile(
+        ~"pub fn f(x : &str) -> u8 { let n = 1_000; for i in 0..n { x.len(); } /* done */ }",
+        RustProfile);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"K(pub)", ~"K(fn)", ~"W(f)", ~"S(()", ~"W(x)", ~"S(:)", ~"S(&)",
+                 ~"W(str)", ~"S())", ~"O(->)", ~"W(u8)", ~"S({)", ~"K(let)", ~"W(n)",
+                 ~"S(=)", ~"N(1_000)", ~"S(;)", ~"K(for)", ~"W(i)", ~"K(in)", ~"N(0)",
+                 ~"O(..)", ~"W(n)", ~"S({)", ~"W(x)", ~"S(.)", ~"W(len)", ~"S(()",
+                 ~"S())", ~"S(;)", ~"S(})", ~"S(})"]);
+
+    let mut corpus = StringTokenizer::new_with_profile(
+        ~"/* outer /* inner */ still a comment */\nfn f<'a>(s: &'a str) -> char {\n    let c = 'x'; // char\n    let e = '\\n';\n    let q = '\\'';\n    'outer: loop { break 'outer; }\n    b'z'\n}\n",
+        RustProfile);
+    assert_eq!(dump_tokens(&mut corpus),
+               ~[~"K(fn)", ~"W(f)", ~"S(<)", ~"C(lifetime:'a)", ~"S(>)", ~"S(()", ~"W(s)",
+                 ~"S(:)", ~"S(&)", ~"C(lifetime:'a)", ~"W(str)", ~"S())", ~"O(->)", ~"W(char)",
+                 ~"S({)", ~"K(let)", ~"W(c)", ~"S(=)", ~"C(char:'x')", ~"S(;)",
+                 ~"K(let)", ~"W(e)", ~"S(=)", ~"C(char:'\\n')", ~"S(;)",
+                 ~"K(let)", ~"W(q)", ~"S(=)", ~"C(char:'\\'')", ~"S(;)",
+                 ~"C(lifetime:'outer)", ~"S(:)", ~"K(loop)", ~"S({)", ~"K(break)",
+                 ~"C(lifetime:'outer)", ~"S(;)", ~"S(})", ~"C(char:b'z')", ~"S(})"]);
+
+    corpus.allow_ip_addresses(true);
+    corpus.set_datas_with_str(~"f32::abs(x); cafe::1; d = dead:1; a::b2::c1(); [2001:db8::1] ::1");
+    assert_eq!(dump_tokens(&mut corpus),
+               ~[~"W(f32)", ~"O(::)", ~"W(abs)", ~"S(()", ~"W(x)", ~"S())", ~"S(;)", ~"W(cafe)",
+                 ~"O(::)", ~"N(1)", ~"S(;)", ~"W(d)", ~"S(=)", ~"W(dead)", ~"S(:)", ~"N(1)",
+                 ~"S(;)", ~"W(a)", ~"O(::)", ~"W(b2)", ~"O(::)", ~"W(c1)", ~"S(()", ~"S())",
+                 ~"S(;)", ~"S([)", ~"IP(2001:db8::1)", ~"S(])", ~"IP(::1)"]);
+}
+
+#[test]
+fn test_python_profile() -> () {
+    let mut st = StringTokenizer::new_with_profile(
+        ~"def f(x):\n    return x ** 2  # square\n",
+        PythonProfile);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"K(def)", ~"W(f)", ~"S(()", ~"W(x)", ~"S())", ~"S(:)", ~"NL",
+                 ~"INDENT", ~"K(return)", ~"W(x)", ~"O(**)", ~"N(2)", ~"NL", ~"DEDENT"]);
+
+    let mut corpus = StringTokenizer::new_with_profile(
+        ~"doc = \"\"\"Say \"hi\"\nit's ok\"\"\"\nraw = r'\\d+' + Rb\"\\x00\" + u'é'\nf\"\"\"{x!r} \"q\" \"\"\"\n'''a''' ''\n",
+        PythonProfile);
+    assert_eq!(dump_tokens(&mut corpus),
+               ~[~"W(doc)", ~"S(=)", ~"W(\"\"\"Say \"hi\"\nit's ok\"\"\")", ~"NL",
+                 ~"W(raw)", ~"S(=)", ~"W(r'\\d+')", ~"S(+)", ~"W(Rb\"\\x00\")", ~"S(+)",
+                 ~"W(u'é')", ~"NL",
+                 ~"SS(f\"\"\")", ~"IS({)", ~"W(x)", ~"S(!)", ~"W(r)", ~"IE(})",
+                 ~"SF( \"q\" )", ~"SE(\"\"\")", ~"NL",
+                 ~"W('''a''')", ~"W('')", ~"NL"]);
+}
+
+#[test]
+fn test_javascript_profile() -> () {
+    let mut st = StringTokenizer::new_with_profile(
+        ~"const s = `t${a}`; if (a === null) { b?.c ??= 0xff; }",
+        JavaScriptProfile);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"K(const)", ~"W(s)", ~"S(=)", ~"SS(`)", ~"SF(t)", ~"IS(${)", ~"W(a)",
+                 ~"IE(})", ~"SE(`)", ~"S(;)", ~"K(if)",
+                 ~"S(()", ~"W(a)", ~"O(===)", ~"NU(null)", ~"S())", ~"S({)", ~"W(b)",
+                 ~"O(?.)", ~"W(c)", ~"O(??=)", ~"N(0xff)", ~"S(;)", ~"S(})"]);
+
+    let mut corpus = StringTokenizer::new_with_profile(
+        ~"// line comment\nconst a = 'it\\'s', b = \"x\";\n/* block\n   comment */\nlet t = `line1\n${a + `n${b}`}`;\n",
+        JavaScriptProfile);
+    assert_eq!(dump_tokens(&mut corpus),
+               ~[~"K(const)", ~"W(a)", ~"S(=)", ~"W('it\\'s')", ~"S(,)", ~"W(b)", ~"S(=)",
+                 ~"W(\"x\")", ~"S(;)", ~"K(let)", ~"W(t)", ~"S(=)", ~"SS(`)", ~"SF(line1\n)",
+                 ~"IS(${)", ~"W(a)", ~"S(+)", ~"SS(`)", ~"SF(n)", ~"IS(${)", ~"W(b)", ~"IE(})",
+                 ~"SE(`)", ~"IE(})", ~"SE(`)", ~"S(;)"]);
+}
+
+#[test]
+fn test_sql_profile() -> () {
+    let mut st = StringTokenizer::new_with_profile(
+        ~"SELECT name, 'it' FROM users -- comment\nWHERE age >= 18 AND id <> 3;",
+        SqlProfile);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"K(SELECT)", ~"W(name)", ~"S(,)", ~"W('it')", ~"K(FROM)", ~"W(users)",
+                 ~"K(WHERE)", ~"W(age)", ~"O(>=)", ~"N(18)", ~"K(AND)", ~"W(id)",
+                 ~"O(<>)", ~"N(3)", ~"S(;)"]);
+
+    let mut corpus = StringTokenizer::new_with_profile(
+        ~"-- comment\nSELECT 'it''s', \"a\"\"b\"\nFROM t /* block */\nWHERE x = '';\n",
+        SqlProfile);
+    assert_eq!(dump_tokens(&mut corpus),
+               ~[~"K(SELECT)", ~"W('it''s')", ~"S(,)", ~"W(\"a\"\"b\")", ~"K(FROM)", ~"W(t)",
+                 ~"K(WHERE)", ~"W(x)", ~"S(=)", ~"W('')", ~"S(;)"]);
+}
+
+#[test]
+fn test_shell_profile() -> () {
+    let mut st = StringTokenizer::new_with_profile(
+        ~"if [ -f $HOME/.rc ]; then\n  echo \"ok\" && exit 0 # done\nfi",
+        ShellProfile);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"K(if)", ~"S([)", ~"W(-f)", ~"SG($:HOME)", ~"W(/.rc)", ~"S(])", ~"S(;)",
+                 ~"K(then)", ~"NL", ~"W(echo)", ~"W(\"ok\")", ~"O(&&)", ~"W(exit)",
+                 ~"N(0)", ~"NL", ~"K(fi)"]);
+
+    let mut corpus = StringTokenizer::new_with_profile(
+        ~"for f in a b; do # loop\n  echo \"$f\" 'x' \\\n    $1\ndone\n",
+        ShellProfile);
+    assert_eq!(dump_tokens(&mut corpus),
+               ~[~"K(for)", ~"W(f)", ~"K(in)", ~"W(a)", ~"W(b)", ~"S(;)", ~"K(do)", ~"NL",
+                 ~"W(echo)", ~"W(\"$f\")", ~"W('x')", ~"SG($:1)", ~"NL", ~"K(done)", ~"NL"]);
+}
+
+#[test]
+fn test_json_profile() -> () {
+    let mut st = StringTokenizer::new_with_profile(
+        ~"{\"a\": [1, -2.5e3, true, null]}",
+        JsonProfile);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"S({)", ~"W(\"a\")", ~"S(:)", ~"S([)", ~"N(1)", ~"S(,)",
+                 ~"N(-2.5e3)", ~"S(,)", ~"B(true:true)", ~"S(,)", ~"NU(null)", ~"S(])", ~"S(})"]);
+
+    let mut corpus = StringTokenizer::new_with_profile(
+        ~"{\n  \"name\": \"a \\\"b\\\"\",\n  \"list\": [\n    1.5e10, -3, false\n  ],\n  \"none\": null\n}\n",
+        JsonProfile);
+    assert_eq!(dump_tokens(&mut corpus),
+               ~[~"S({)", ~"W(\"name\")", ~"S(:)", ~"W(\"a \\\"b\\\"\")", ~"S(,)", ~"W(\"list\")",
+                 ~"S(:)", ~"S([)", ~"N(1.5e10)", ~"S(,)", ~"N(-3)", ~"S(,)", ~"B(false:false)",
+                 ~"S(])", ~"S(,)", ~"W(\"none\")", ~"S(:)", ~"NU(null)", ~"S(})"]);
+}
+
+#[test]
+fn test_settings_documents() -> () {
+    let mut st = StringTokenizer::new_with_str(~"");
+    let toml = "profile = \"c\" # base profile\nkeywords = [\"let\",\n    \"fn\"]\nsingle_line_comment = '#'\n";
+
+    assert!(st.load_settings_from_toml(toml).is_ok());
+    st.set_datas_with_str(~"let x # comment\nfn");
+    assert_eq!(dump_tokens(&mut st), ~[~"K(let)", ~"W(x)", ~"K(fn)"]);
+
+    let mut fromJson = StringTokenizer::new_with_str(~"");
+    let mut fromToml = StringTokenizer::new_with_str(~"");
+    assert!(fromJson.load_settings_from_json(st.settings_to_json()).is_ok());
+    assert!(fromToml.load_settings_from_toml(st.settings_to_toml()).is_ok());
+    assert_eq!(fromJson.settings_to_toml(), st.settings_to_toml());
+    assert_eq!(fromToml.settings_to_json(), st.settings_to_json());
+
+    let sample = ~"def f(a,\n      b): # c\n    x = f'{a + b}' \\\n        + use(v1.2)\n\n\n    return match\u2028None or True\n";
+    let mut python = StringTokenizer::new_with_profile(sample.clone(), PythonProfile);
+    python.set_unicode_new_lines(true);
+    python.collapse_blank_lines(true);
+    python.set_lossless(true);
+    assert!(python.add_rule(~"version", "v[0-9]+(\\.[0-9]+)+", -1).is_ok());
+    let mut reloaded = StringTokenizer::new_with_str(sample.clone());
+    assert!(reloaded.load_settings_from_toml(python.settings_to_toml()).is_ok());
+    assert_eq!(reloaded.settings_to_toml(), python.settings_to_toml());
+    let mut fromDocument = StringTokenizer::new_with_str(sample);
+    assert!(fromDocument.load_settings_from_json(python.settings_to_json()).is_ok());
+    let expected = lossless_dump(&mut python);
+    assert_eq!(lossless_dump(&mut reloaded), expected.clone());
+    assert_eq!(lossless_dump(&mut fromDocument), expected);
+}
+
+#[cfg(test)]
+fn lossless_dump(st : &mut StringTokenizer) -> ~[~str] {
+    let mut tokens = ~[];
+
+    while st.has_token() {
+        tokens.push(fmt!("%?:%s:%s:%s", st.token, st.leadingTrivia, st.get_token_text(),
+                         st.trailingTrivia));
+    }
+    tokens
+}
+
+#[test]
+fn test_settings_errors() -> () {
+    let mut st = StringTokenizer::new_with_str(~"");
+
+    match st.load_settings_from_toml("keywords = [\"a\"]\ncomment = \"#\"\n") {
+        Ok(_)       => fail!(~"unknown key accepted"),
+        Err(error)  => assert_eq!(error.key, ~"comment")
+    }
+    match st.load_settings_from_json("{\"hex_numbers\": true, \"delimiters\": [\" \", \"ab\"]}") {
+        Ok(_)       => fail!(~"invalid delimiter accepted"),
+        Err(error)  => assert_eq!(error.key, ~"delimiters")
+    }
+    match st.load_settings_from_toml("rules = [\"a\", \"[a-\", \"0\"]\n") {
+        Ok(_)       => fail!(~"invalid rule accepted"),
+        Err(error)  => assert_eq!(error.key, ~"rules")
+    }
+    assert!(!st.hexNumbers);
+}
+
+#[test]
+fn test_custom_rules() -> () {
+    let mut st = StringTokenizer::new_with_str(
+        ~"color #ff00aa since v1.2.3 on 2024-10-18 by @bob id 123e4567-e89b-12d3-a456-426614174000 x-1");
+
+    assert!(st.add_rule(~"color", "#[0-9a-f]{6}", 0).is_ok());
+    assert!(st.add_rule(~"version", "v[0-9]+(\\.[0-9]+){2}", 0).is_ok());
+    assert!(st.add_rule(~"uuid", "[0-9a-f]{8}(-[0-9a-f]{4}){3}-[0-9a-f]{12}", 0).is_ok());
+    assert!(st.add_rule(~"date", "[0-9]{4}-[0-9]{2}-[0-9]{2}", 0).is_ok());
+    assert!(st.add_rule(~"mention", "@[A-Za-z_][A-Za-z0-9_]*", 0).is_ok());
+    assert!(st.add_rule(~"broken", "[a-", 0).is_err());
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"W(color)", ~"C(color:#ff00aa)", ~"W(since)", ~"C(version:v1.2.3)", ~"W(on)",
+                 ~"C(date:2024-10-18)", ~"W(by)", ~"C(mention:@bob)", ~"W(id)",
+                 ~"C(uuid:123e4567-e89b-12d3-a456-426614174000)", ~"W(x)", ~"S(-)", ~"N(1)"]);
+}
+
+#[cfg(test)]
+fn bench_source() -> ~str {
+    let mut source = ~"";
+
+    for 200.times {
+        source.push_str("int keyword42(int value) { /* loop */ return value >= 0x10 ? value : -1.5e3; }\n");
+    }
+    source
+}
+
+#[bench]
+fn bench_has_token_many_keywords(bh : &mut BenchHarness) -> () {
+    let source = bench_source();
+    let mut st = StringTokenizer::new_with_profile(source.clone(), CProfile);
+
+    for uint::range(0, 500) |i| {
+        st.add_keyword(fmt!("keyword%u", i));
+    }
+    do bh.iter {
+        st.set_datas(str_to_vec(source.clone()));
+        while st.has_token() {}
+    }
+}
+
+#[bench]
+fn bench_linear_lookups_many_keywords(bh : &mut BenchHarness) -> () {
+    // the lookups done by has_token before the dictionnaries were compiled :
+    // each char is searched in the delimiters and the special chars and each
+    // word in the keywords, with the same dictionnaries as bench_has_token_many_keywords
+    let source = bench_source();
+    let datas = str_to_vec(source.clone());
+    let st = StringTokenizer::new_with_profile(source, CProfile);
+    let mut keyWords = st.keyWords.clone();
+
+    for uint::range(0, 500) |i| {
+        keyWords.push(fmt!("keyword%u", i));
+    }
+    do bh.iter {
+        let mut pos = 0;
+        let mut found = 0;
+        while pos < datas.len() {
+            if st.delimiters.iter().any(|delim| *delim == datas[pos]) || datas[pos] == '\n'
+                || st.specialChars.iter().any(|spec| *spec == datas[pos]) {
+                pos += 1;
+            }
+            else {
+                let mut word = ~"";
+                while pos < datas.len() && datas[pos] != '\n'
+                    && !st.delimiters.iter().any(|delim| *delim == datas[pos])
+                    && !st.specialChars.iter().any(|spec| *spec == datas[pos]) {
+                    word.push_char(datas[pos]);
+                    pos += 1;
+                }
+                if keyWords.iter().any(|keyword| *keyword == word) {
+                    found += 1;
+                }
+            }
         }
+        assert!(found > 0);
+    }
+}
+
+#[bench]
+fn bench_has_token_profile_keywords(bh : &mut BenchHarness) -> () {
+    // the same datas with only the keywords of the profile, the lookups of
+    // bench_has_token_many_keywords must not be slower with 500 more keywords
+    let source = bench_source();
+    let mut st = StringTokenizer::new_with_profile(source.clone(), CProfile);
+
+    do bh.iter {
+        st.set_datas(str_to_vec(source.clone()));
+        while st.has_token() {}
     }
 }
 
 #[test]
-fn test_with_strotkenierrs() -> () {
-    let path = ~PosixPath("./strtokenizer.rs");
-    let file = match io::read_whole_file_str(path) {
-        Ok(file)        => file,
-        Err(error)      => fail!(fmt!("Error during file reading :\n%s", error))
-    };
-    let i = 42;
-    let j = 42.42;
-    let h = 2345678;
-    //io::println(fmt!("File main.rs :\n\n%s\nTokens :\n\n", file));
-    let mut st = StringTokenizer::new_with_str(file);
-    st.set_comments(AllComments);
+fn test_lexer_modes() -> () {
+    let mut st = StringTokenizer::new_with_str(~"if {{ if x }} if {{ {{ }} if");
+    let mut tokens = ~[];
+
+    st.add_operator(~"{{");
+    assert!(st.add_mode(~"expr"));
+    assert!(!st.add_mode(~"expr"));
+    assert!(st.add_mode_transition(~"{{", PushMode(~"expr")));
+    assert!(!st.add_mode_transition(~"<%", PushMode(~"unknown")));
+    assert!(st.select_mode("expr"));
+    st.add_operator(~"}}");
+    st.add_keyword(~"if");
+    assert!(st.add_mode_transition(~"}}", PopMode));
+    assert!(st.select_mode("default"));
+    while st.has_token() {
+        tokens.push(fmt!("%s@%s", st.get_token_text(), st.mode));
+    }
+    assert_eq!(tokens, ~[~"if@default", ~"{{@default", ~"if@expr", ~"x@expr", ~"}}@expr",
+                         ~"if@default", ~"{{@default", ~"{@expr", ~"{@expr", ~"}}@expr",
+                         ~"if@default"]);
+    assert_eq!(st.get_mode(), ~"default");
+}
+
+#[test]
+fn test_interpolated_strings() -> () {
+    let mut st = StringTokenizer::new_with_str(~"\"Hi ${f({a}) + \"<${n}>\"}!\" + 1");
+
+    st.add_interpolated_string(~"", '"', ~"${", ~"}");
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"SS(\")", ~"SF(Hi )", ~"IS(${)", ~"W(f)", ~"S(()", ~"S({)", ~"W(a)", ~"S(})",
+                 ~"S())", ~"S(+)", ~"SS(\")", ~"SF(<)", ~"IS(${)", ~"W(n)", ~"IE(})", ~"SF(>)",
+                 ~"SE(\")", ~"IE(})", ~"SF(!)", ~"SE(\")", ~"S(+)", ~"N(1)"]);
+
+    let mut python = StringTokenizer::new_with_profile(~"f'{x+1}' + \"{y}\"", PythonProfile);
+    assert_eq!(dump_tokens(&mut python),
+               ~[~"SS(f')", ~"IS({)", ~"W(x)", ~"S(+)", ~"N(1)", ~"IE(})", ~"SE(')", ~"S(+)",
+                 ~"W(\"{y}\")"]);
+}
+
+#[test]
+fn test_offside_rule() -> () {
+    let mut st = StringTokenizer::new_with_str(
+        ~"if a:\n    if b:\n        c\n\n    d\ne\n  f\n\tg\n");
+
+    st.set_offside_rule(true);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"W(if)", ~"W(a)", ~"S(:)", ~"NL",
+                 ~"INDENT", ~"W(if)", ~"W(b)", ~"S(:)", ~"NL",
+                 ~"INDENT", ~"W(c)", ~"NL", ~"NL",
+                 ~"DEDENT", ~"W(d)", ~"NL",
+                 ~"DEDENT", ~"W(e)", ~"NL",
+                 ~"INDENT", ~"W(f)", ~"NL",
+                 ~"E(mixed tabs and spaces in indentation)", ~"W(g)", ~"NL",
+                 ~"DEDENT"]);
+
+    st.set_datas(str_to_vec(~"x = [\n    1,\n]\nf(a,\n  (b)\n)\nif x:\n    y\n"));
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"W(x)", ~"S(=)", ~"S([)", ~"N(1)", ~"S(,)", ~"S(])", ~"NL",
+                 ~"W(f)", ~"S(()", ~"W(a)", ~"S(,)", ~"S(()", ~"W(b)", ~"S())", ~"S())", ~"NL",
+                 ~"W(if)", ~"W(x)", ~"S(:)", ~"NL", ~"INDENT", ~"W(y)", ~"NL", ~"DEDENT"]);
+
+    st.set_datas(str_to_vec(~"a\n    b\n  c\n"));
     st.set_new_line_as_token(false);
-    st.add_keyword(~"let");
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"W(a)", ~"INDENT", ~"W(b)",
+                 ~"E(dedent does not match any outer indentation)", ~"W(c)", ~"DEDENT"]);
+}
+
+#[test]
+fn test_newline_tokens() -> () {
+    let mut st = StringTokenizer::new_with_str(~"a\r\nb\rc\n\n  \nd\u2028e");
+
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"W(a)", ~"NL", ~"W(b)", ~"NL", ~"W(c)", ~"NL", ~"NL", ~"NL", ~"W(d\u2028e)"]);
+
+    st.set_unicode_new_lines(true);
+    st.collapse_blank_lines(true);
+    st.set_datas_with_str(~"a\r\n\r\n  \nb\u2028c");
+    assert!(st.has_token());
+    assert_eq!(st.span, Span { begin : 0, end : 1 });
+    assert!(st.has_token());
+    assert_eq!(st.newline, ~"\r\n\r\n  \n");
+    assert_eq!(st.span, Span { begin : 1, end : 8 });
+    assert!(st.has_token());
+    assert_eq!(st.span, Span { begin : 8, end : 9 });
+    assert!(st.has_token());
+    assert_eq!(st.newline, ~"\u2028");
+    assert_eq!(st.span, Span { begin : 9, end : 10 });
+    assert!(st.has_token());
+    assert_eq!(st.span, Span { begin : 10, end : 11 });
+    assert!(!st.has_token());
+
+    st.set_new_line_as_token(false);
+    st.set_datas_with_str(~"a\r\nb\rc");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(a)", ~"W(b)", ~"W(c)"]);
+
+    st.set_new_line_as_token(true);
+    st.set_comments(AllComments);
+    st.set_datas_with_str(~"a // x\rb // y\r\nc // z");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(a)", ~"NL", ~"W(b)", ~"NL", ~"W(c)"]);
+}
+
+#[test]
+fn test_line_continuation() -> () {
+    let mut st = StringTokenizer::new_with_str(~"ab\\\ncd e\\\r\n  f");
+
+    st.set_line_continuation(Some(~"\\"));
+    assert!(st.has_token());
+    assert_eq!(st.word, ~"abcd");
+    assert_eq!(st.span, Span { begin : 0, end : 6 });
+    assert!(st.has_token());
+    assert_eq!(st.word, ~"e");
+    assert_eq!(st.span, Span { begin : 7, end : 8 });
+    assert!(st.has_token());
+    assert_eq!(st.word, ~"f");
+    assert_eq!(st.span, Span { begin : 13, end : 14 });
+    assert!(!st.has_token());
+
+    let mut shell = StringTokenizer::new_with_profile(~"ec\\\nho a \\\n  b\nc", ShellProfile);
+    assert_eq!(dump_tokens(&mut shell), ~[~"W(echo)", ~"W(a)", ~"W(b)", ~"NL", ~"W(c)"]);
+}
+
+#[cfg(test)]
+fn lossless_text(st : &mut StringTokenizer) -> ~str {
+    let mut text = ~"";
+
+    st.set_lossless(true);
+    while st.has_token() {
+        text.push_str(st.get_full_text());
+    }
+    text
+}
+
+#[test]
+fn test_lossless() -> () {
+    let mut st = StringTokenizer::new_with_str(~" a /* c */ b // d\n  c\n");
+
+    st.set_comments(AllComments);
+    st.set_lossless(true);
+    assert!(st.has_token());
+    assert_eq!((st.leadingTrivia.clone(), st.word.clone(), st.trailingTrivia.clone()),
+               (~" ", ~"a", ~" /* c */ "));
+    assert!(st.has_token());
+    assert_eq!((st.leadingTrivia.clone(), st.word.clone(), st.trailingTrivia.clone()),
+               (~"", ~"b", ~" // d"));
+    assert!(st.has_token());
+    assert_eq!((st.token, st.get_full_text()), (Newline, ~"\n"));
+    assert!(st.has_token());
+    assert_eq!((st.leadingTrivia.clone(), st.word.clone(), st.trailingTrivia.clone()),
+               (~"  ", ~"c", ~""));
+    assert!(st.has_token());
+    assert_eq!(st.get_full_text(), ~"\n");
+    assert!(st.has_token());
+    assert_eq!(st.span, Span { begin : 22, end : 22 });
+    assert!(!st.has_token());
+
+    // Property : the concatenation of the full texts is the datas
+    let alphabet = str_to_vec(~"ab1 .\t\n\r\\/*#\"'{}$+=`-x");
+    let profiles = [CProfile, PythonProfile, ShellProfile, JavaScriptProfile];
+    let mut seed = 42u;
+    for 300.times {
+        let mut input = ~"";
+        seed = seed * 1103515245 + 12345;
+        for uint::range(0, seed / 65536 % 40) |_| {
+            seed = seed * 1103515245 + 12345;
+            input.push_char(alphabet[seed / 65536 % alphabet.len()]);
+        }
+        let mut default = StringTokenizer::new_with_str(input.clone());
+        default.set_comments(AllComments);
+        assert_eq!(lossless_text(&mut default), input.clone());
+        for profiles.iter().advance |profile| {
+            let mut st = StringTokenizer::new_with_profile(input.clone(), *profile);
+            assert_eq!(lossless_text(&mut st), input.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+fn collect_tokens(st : &mut StringTokenizer) -> ~[TokenData] {
+    let mut tokens = ~[];
+
+    while st.has_token() {
+        tokens.push(st.get_token_data());
+    }
+    tokens
+}
+
+#[test]
+fn test_print_tokens() -> () {
+    let mut st = StringTokenizer::new_with_profile(~"int  x = a+ +b; // c\n", CProfile);
+    let tokens = collect_tokens(&mut st);
+    assert_eq!(st.print_tokens(tokens), ~"int x=a+ +b;");
+
+    let mut python = StringTokenizer::new_with_profile(~"if a :\n  return  - 1\n", PythonProfile);
+    let tokens = collect_tokens(&mut python);
+    assert_eq!(python.print_tokens(tokens), ~"if a:\n    return-1\n");
+
+    let source = ~"a  /* k */ b\n";
+    let mut lossless = StringTokenizer::new_with_profile(source.clone(), CProfile);
+    lossless.set_lossless(true);
+    let mut tokens = collect_tokens(&mut lossless);
+    assert_eq!(lossless.print_tokens_with_trivia(tokens), source);
+    tokens.insert(1, TokenData {
+        token : Word,
+        text : ~"c",
+        leadingTrivia : ~"",
+        trailingTrivia : ~"",
+        span : Span { begin : 0, end : 0 },
+        mode : ~"default"
+    });
+    assert_eq!(lossless.print_tokens_with_trivia(tokens), ~"a  /* k */ c b\n");
+}
+
+#[test]
+fn test_minify() -> () {
+    let mut json = StringTokenizer::new_with_profile(
+        ~"{ \"a\" : [1, -2.5e3, true],\n  \"b c\" : null }\n", JsonProfile);
+    assert_eq!(json.minify(), ~"{\"a\":[1,-2.5e3,true],\"b c\":null}");
+
+    let mut css = StringTokenizer::new_with_profile(
+        ~"/* x */\nbody  .main > p {\n  font-size : 12px ;\n  color : #fff !important;\n}\n",
+        CssProfile);
+    assert_eq!(css.minify(), ~"body .main>p{font-size:12px;color:#fff !important;}");
+
+    let mut c = StringTokenizer::new_with_profile(
+        ~"int main ( ) {\n    return a - -1 ; // done\n} /* end */", CProfile);
+    assert_eq!(c.minify(), ~"int main(){return a- -1;}");
+
+    let mut directives = StringTokenizer::new_with_profile(
+        ~"#include <a.h>\n#define A 1\n#define B(x) \\\n    (x + 1)\n\nint x = B(A); // #\n", CProfile);
+    assert_eq!(directives.minify(),
+               ~"#include<a.h>\n#define A 1\n#define B(x)(x+1)\nint x=B(A);");
+
+    let mut python = StringTokenizer::new_with_profile(
+        ~"\n# c\na = 'x  y'\n\n\nif a :\n    b()\n", PythonProfile);
+    assert_eq!(python.minify(), ~"a='x  y'\nif a:\n b()\n");
+
+    let mut modes = StringTokenizer::new_with_str(~"a - - b {{ c - - d }}");
+    modes.add_specialchar('-');
+    modes.add_operator(~"{{");
+    modes.add_mode(~"expr");
+    modes.add_mode_transition(~"{{", PushMode(~"expr"));
+    modes.select_mode("expr");
+    modes.add_specialchar('-');
+    modes.add_operator(~"--");
+    modes.add_operator(~"}}");
+    modes.add_mode_transition(~"}}", PopMode);
+    modes.select_mode("default");
+    assert_eq!(modes.minify(), ~"a--b{{c- -d}}");
+}
+
+#[cfg(test)]
+fn dump_trees(trees : &[TokenTree]) -> ~[~str] {
+    let mut dump = ~[];
+
+    for trees.iter().advance |tree| {
+        match *tree {
+            Leaf(ref token)     => dump.push(token.text.clone()),
+            Group(ref group)    => {
+                dump.push(fmt!("%c%s", group.delimiter, dump_trees(group.children).connect(" ")));
+            }
+        }
+    }
+    dump
+}
+
+#[test]
+fn test_token_trees() -> () {
+    let mut st = StringTokenizer::new_with_str(~"f(a, [b {c}]) <d>");
+
+    st.set_bracket_pairs([('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')]);
+    match st.token_trees() {
+        Ok(trees)   => {
+            assert_eq!(dump_trees(trees), ~[~"f", ~"(a , [b {c", ~"<d"]);
+            match trees[1] {
+                Group(ref group)    => {
+                    assert_eq!(group.openSpan, Span { begin : 1, end : 2 });
+                    assert_eq!(group.closeSpan, Span { begin : 12, end : 13 });
+                }
+                Leaf(_)             => fail!(~"expected a group")
+            }
+        }
+        Err(error)  => fail!(error.message)
+    }
+
+    st.set_datas_with_str(~"a ( b ]");
+    match st.token_trees() {
+        Ok(_)       => fail!(~"expected a mismatched bracket"),
+        Err(error)  => {
+            assert_eq!(error.openSpan, Some(Span { begin : 2, end : 3 }));
+            assert_eq!(error.closeSpan, Some(Span { begin : 6, end : 7 }));
+        }
+    }
+    st.set_datas_with_str(~"a )");
+    match st.token_trees() {
+        Ok(_)       => fail!(~"expected an unmatched bracket"),
+        Err(error)  => assert_eq!((error.openSpan, error.closeSpan),
+                                  (None, Some(Span { begin : 2, end : 3 })))
+    }
+    st.set_datas_with_str(~"( [ ]");
+    match st.token_trees() {
+        Ok(_)       => fail!(~"expected an unclosed bracket"),
+        Err(error)  => assert_eq!((error.openSpan, error.closeSpan),
+                                  (Some(Span { begin : 0, end : 1 }), None))
+    }
+}
+
+#[test]
+fn test_case_insensitive_keywords() -> () {
+    let mut st = StringTokenizer::new_with_profile(~"select Name from T WHERE x", SqlProfile);
+
+    assert_eq!(dump_tokens(&mut st), ~[~"K(SELECT)", ~"W(Name)", ~"K(FROM)", ~"W(T)",
+                                       ~"K(WHERE)", ~"W(x)"]);
+
+    st.reset_settings();
+    st.add_delimiter(' ');
+    st.add_keyword(~"Straße");
+    st.add_keyword(~"ΛΑΜΒΔΑ");
+    st.set_datas_with_str(~"straße Straße λαμβδα");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(straße)", ~"K(Straße)", ~"W(λαμβδα)"]);
+    st.set_case_insensitive_keywords(true);
+    st.set_datas_with_str(~"STRAßE straße λαμβδα Λαμβδα λάμβδα");
+    assert!(st.has_token());
+    assert_eq!((st.word.clone(), st.keyword.clone()), (~"STRAßE", ~"Straße"));
+    assert_eq!(dump_tokens(&mut st), ~[~"K(Straße)", ~"K(ΛΑΜΒΔΑ)", ~"K(ΛΑΜΒΔΑ)", ~"W(λάμβδα)"]);
+}
+
+#[test]
+fn test_keyword_ids() -> () {
+    let mut st = StringTokenizer::new_with_str(~"int x = true; while y");
+
+    st.add_keyword_with_id(~"int", 10, TypeKeyword);
+    st.add_keyword_with_id(~"true", 20, LiteralKeyword);
+    st.add_keyword_with_id(~"while", 30, ControlKeyword);
+    st.add_keyword(~"y");
+    let mut kinds = ~[];
     while st.has_token() {
         match st.token {
-            SpecialChar   => io::println(fmt!("SPECIAL CHAR : %c", st.specialChar)),
-            Word          => io::println(fmt!("WORD : %s", st.word)),
-            Number        => io::println(fmt!("NUMBER : %s", st.number)),
-            KeyWord       => io::println(fmt!("KEYWORD : %s", st.keyword)),
-            NoToken       => {}
+            KeyWord => kinds.push((st.keyword.clone(), st.keywordId, st.keywordCategory)),
+            _       => {}
+        }
+    }
+    assert_eq!(kinds, ~[(~"int", 10, TypeKeyword), (~"true", 20, LiteralKeyword),
+                        (~"while", 30, ControlKeyword), (~"y", 3, OtherKeyword)]);
+}
+
+#[test]
+fn test_soft_keywords() -> () {
+    let mut st = StringTokenizer::new_with_str(~"union U { x } let union = 1; union V {}");
+
+    st.add_keyword(~"let");
+    st.add_soft_keyword(~"union");
+    assert!(st.has_token());
+    assert_eq!((st.word.clone(), st.maybeKeyword, st.keywordCategory),
+               (~"union", true, ContextualKeyword));
+    assert!(st.has_token());
+    assert!(!st.maybeKeyword);
+
+    let predicate : @fn(&str, &[TokenData]) -> bool = |_, previous| {
+        match previous.last_opt() {
+            Some(token) => token.text == ~"}" || token.text == ~";",
+            None        => true
         }
+    };
+    st.set_soft_keyword_predicate(Some(predicate));
+    st.set_datas_with_str(~"union U { x } let union = 1; union V {}");
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"K(union)", ~"W(U)", ~"S({)", ~"W(x)", ~"S(})", ~"K(let)", ~"W(union)",
+                 ~"S(=)", ~"N(1)", ~"S(;)", ~"K(union)", ~"W(V)", ~"S({)", ~"S(})"]);
+}
+
+#[test]
+fn test_literals() -> () {
+    let mut st = StringTokenizer::new_with_profile(~"x = True or None", PythonProfile);
+
+    assert_eq!(dump_tokens(&mut st), ~[~"W(x)", ~"S(=)", ~"B(True:true)", ~"K(or)", ~"NU(None)"]);
+    st.set_profile(SqlProfile);
+    st.set_datas_with_str(~"SET a = false WHERE b IS null");
+    assert_eq!(dump_tokens(&mut st), ~[~"K(SET)", ~"W(a)", ~"S(=)", ~"B(false:false)",
+                                       ~"K(WHERE)", ~"W(b)", ~"K(IS)", ~"NU(null)"]);
+
+    st.reset_settings();
+    st.add_delimiter(' ');
+    st.add_bool_literal(~"yes", true);
+    st.add_bool_literal(~"off", false);
+    st.add_null_literal(~"nil");
+    st.set_datas_with_str(~"yes off nil no");
+    assert_eq!(dump_tokens(&mut st), ~[~"B(yes:true)", ~"B(off:false)", ~"NU(nil)", ~"W(no)"]);
+}
+
+#[test]
+fn test_signed_numbers() -> () {
+    let mut st = StringTokenizer::new_with_str(~"a - 1 x == -1, [+2, -Infinity] (NaN) f() -3");
+
+    st.add_delimiter(' ');
+    for ['-', '+', ',', '[', ']', '(', ')'].iter().advance |specialChar| {
+        st.add_specialchar(*specialChar);
+    }
+    st.add_operator(~"==");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(a)", ~"S(-)", ~"N(1)", ~"W(x)", ~"O(==)", ~"S(-)",
+                                       ~"N(1)", ~"S(,)", ~"S([)", ~"S(+)", ~"N(2)", ~"S(,)", ~"S(-)",
+                                       ~"W(Infinity)",
+                                       ~"S(])", ~"S(()", ~"W(NaN)", ~"S())", ~"W(f)", ~"S(()",
+                                       ~"S())", ~"S(-)", ~"N(3)"]);
+
+    st.allow_signed_numbers(true);
+    st.set_datas_with_str(~"a - 1 x == -1, [+2, -Infinity] (NaN) f() -3\n-4 - -5");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(a)", ~"S(-)", ~"N(1)", ~"W(x)", ~"O(==)", ~"N(-1)",
+                                       ~"S(,)", ~"S([)", ~"N(+2)", ~"S(,)", ~"N(-Infinity)",
+                                       ~"S(])", ~"S(()", ~"N(NaN)", ~"S())", ~"W(f)", ~"S(()",
+                                       ~"S())", ~"S(-)", ~"N(3)", ~"NL", ~"N(-4)", ~"S(-)",
+                                       ~"N(-5)"]);
+}
+
+#[test]
+fn test_units() -> () {
+    let mut st = StringTokenizer::new_with_str(~"10px 1.5em 50%, 30s 2GiB 3ms 4 kg x10px, -2px");
+
+    st.add_delimiter(' ');
+    st.add_specialchar('%');
+    st.add_specialchar(',');
+    st.add_specialchar('-');
+    for ["px", "em", "%", "s", "ms", "GiB"].iter().advance |unit| {
+        st.add_unit(unit.to_owned());
+    }
+    st.allow_signed_numbers(true);
+    assert_eq!(dump_tokens(&mut st), ~[~"D(10:px)", ~"D(1.5:em)", ~"D(50:%)", ~"S(,)", ~"D(30:s)",
+                                       ~"D(2:GiB)", ~"D(3:ms)", ~"N(4)", ~"W(kg)", ~"W(x10px)",
+                                       ~"S(,)", ~"D(-2:px)"]);
+
+    st.set_profile(CssProfile);
+    st.set_datas_with_str(~"p{width:50%;font-size:1.5em}");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(p)", ~"S({)", ~"W(width)", ~"S(:)", ~"D(50:%)", ~"S(;)",
+                                       ~"W(font-size)", ~"S(:)", ~"D(1.5:em)", ~"S(})"]);
+}
+
+#[test]
+fn test_dotted_numbers() -> () {
+    let mut st = StringTokenizer::new_with_str(~"1.2.3 1.0.0-rc.1+build.5 192.168.0.1:8080 2024.01.18");
+
+    st.add_delimiter(' ');
+    for [':', '.', '-', '+'].iter().advance |specialChar| {
+        st.add_specialchar(*specialChar);
+    }
+    assert_eq!(dump_tokens(&mut st), ~[~"N(1.2)", ~"S(.)", ~"N(3)", ~"N(1.0)", ~"S(.)", ~"N(0)",
+                                       ~"S(-)", ~"W(rc)", ~"S(.)", ~"N(1)", ~"S(+)", ~"W(build)",
+                                       ~"S(.)", ~"N(5)", ~"N(192.168)", ~"S(.)", ~"N(0.1)",
+                                       ~"S(:)", ~"N(8080)", ~"N(2024.01)", ~"S(.)", ~"N(18)"]);
+
+    st.allow_versions(true);
+    st.allow_ip_addresses(true);
+    st.allow_dotted_numbers(true);
+    st.set_datas_with_str(~"1.2.3 1.0.0-rc.1+build.5 192.168.0.1:8080 2024.01.18 fe80::0:1 ::1");
+    assert_eq!(dump_tokens(&mut st), ~[~"V(1.2.3)", ~"V(1.0.0-rc.1+build.5)", ~"IP(192.168.0.1)",
+                                       ~"S(:)", ~"N(8080)", ~"DN(2024.01.18)", ~"IP(fe80::0:1)",
+                                       ~"IP(::1)"]);
+    st.set_datas_with_str(~"add::bad 1.5 256.1.1.1 2001:db8:0:0:0:0:2:1 1:2:3 1.2.3-");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(add)", ~"S(:)", ~"S(:)", ~"W(bad)", ~"N(1.5)",
+                                       ~"DN(256.1.1.1)", ~"IP(2001:db8:0:0:0:0:2:1)", ~"N(1)",
+                                       ~"S(:)", ~"N(2)", ~"S(:)", ~"N(3)", ~"V(1.2.3)", ~"S(-)"]);
+}
+
+#[cfg(test)]
+fn dump_timestamp(timestamp : &Timestamp) -> ~str {
+    let offset = match timestamp.offset {
+        Some(offset)    => fmt!("%d", offset),
+        None            => ~"-"
+    };
+
+    fmt!("%u-%u-%u %u:%u:%u.%u %s", timestamp.year, timestamp.month, timestamp.day,
+         timestamp.hour, timestamp.minute, timestamp.second, timestamp.nanosecond, offset)
+}
+
+#[test]
+#[cfg(not(date_times))]
+fn test_date_times_not_built() -> () {
+    let mut st = StringTokenizer::new_with_str(~"2024-10-18 12:30:00 1h30m");
+
+    st.add_delimiter(' ');
+    for [':', '-', '+', '.'].iter().advance |specialChar| {
+        st.add_specialchar(*specialChar);
+    }
+    st.allow_date_times(true);
+    assert_eq!(dump_tokens(&mut st), ~[~"N(2024)", ~"S(-)", ~"N(10)", ~"S(-)", ~"N(18)", ~"N(12)",
+                                       ~"S(:)", ~"N(30)", ~"S(:)", ~"N(00)", ~"W(1h30m)"]);
+}
+
+#[test]
+#[cfg(date_times)]
+fn test_date_times() -> () {
+    let mut st = StringTokenizer::new_with_str(~"2024-10-18 12:30:00 1h30m");
+
+    st.add_delimiter(' ');
+    for [':', '-', '+', '.'].iter().advance |specialChar| {
+        st.add_specialchar(*specialChar);
+    }
+    assert_eq!(dump_tokens(&mut st), ~[~"N(2024)", ~"S(-)", ~"N(10)", ~"S(-)", ~"N(18)", ~"N(12)",
+                                       ~"S(:)", ~"N(30)", ~"S(:)", ~"N(00)", ~"W(1h30m)"]);
+
+    st.allow_date_times(true);
+    st.set_datas_with_str(~"2024-10-18 2024-10-18T12:30:05.25+02:00 2024-10-18T00:00Z 12:30:00");
+    assert_eq!(dump_tokens(&mut st), ~[~"DT(2024-10-18:2024-10-18 0:0:0.0 -)",
+                                       ~"DT(2024-10-18T12:30:05.25+02:00:2024-10-18 12:30:5.250000000 120)",
+                                       ~"DT(2024-10-18T00:00Z:2024-10-18 0:0:0.0 0)",
+                                       ~"DT(12:30:00:0-0-0 12:30:0.0 -)"]);
+    st.set_datas_with_str(~"1h30m 1.5s 500ms 2024-13-01 12:30 1min");
+    assert_eq!(dump_tokens(&mut st), ~[~"DU(1h30m:5400000000000)", ~"DU(1.5s:1500000000)",
+                                       ~"DU(500ms:500000000)", ~"N(2024)", ~"S(-)", ~"N(13)",
+                                       ~"S(-)", ~"N(01)", ~"N(12)", ~"S(:)", ~"N(30)", ~"W(1min)"]);
+}
+
+#[test]
+fn test_locations() -> () {
+    let mut st = StringTokenizer::new_with_profile(~"see http://example.com/a", CProfile);
+
+    assert_eq!(dump_tokens(&mut st), ~[~"W(see)", ~"W(http)", ~"S(:)"]);
+    st.allow_urls(true);
+    st.allow_emails(true);
+    st.allow_paths(true);
+    st.set_datas_with_str(~"see http://example.com/a/b?c=d#e, (https://x.org/p_(1)) jane.doe+tag@example.com.");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(see)", ~"U(http://example.com/a/b?c=d#e)", ~"S(,)", ~"S(()",
+                                       ~"U(https://x.org/p_(1))", ~"S())",
+                                       ~"M(jane.doe+tag@example.com)", ~"S(.)"]);
+    st.set_datas_with_str(~"./run.sh (/usr/bin/env) C:\\Users\\me a/b x[1]/2 \\\\server\\share a@b");
+    assert_eq!(dump_tokens(&mut st), ~[~"P(./run.sh)", ~"S(()", ~"P(/usr/bin/env)", ~"S())",
+                                       ~"P(C:\\Users\\me)", ~"W(a)", ~"S(/)", ~"W(b)", ~"W(x)",
+                                       ~"S([)", ~"N(1)", ~"S(])", ~"S(/)", ~"N(2)",
+                                       ~"P(\\\\server\\share)", ~"W(a@b)"]);
+    st.set_datas_with_str(~"x = a /b + 1 / c;\nx = /tmp/log;");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(x)", ~"S(=)", ~"W(a)", ~"S(/)", ~"W(b)", ~"S(+)",
+                                       ~"N(1)", ~"S(/)", ~"W(c)", ~"S(;)", ~"W(x)", ~"S(=)",
+                                       ~"P(/tmp/log)", ~"S(;)"]);
+
+    st.set_profile(ShellProfile);
+    st.allow_paths(true);
+    st.set_datas_with_str(~"ls /tmp /usr/lib");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(ls)", ~"P(/tmp)", ~"P(/usr/lib)"]);
+}
+
+#[test]
+fn test_sigils() -> () {
+    let mut st = StringTokenizer::new_with_str(~"$var @attr #tag %macro $ x $(cmd) @1 #été a$b");
+
+    st.add_delimiter(' ');
+    for ['$', '@', '#', '%', '(', ')'].iter().advance |specialChar| {
+        st.add_specialchar(*specialChar);
+    }
+    for ['$', '@', '#', '%'].iter().advance |sigil| {
+        st.add_sigil(*sigil);
+    }
+    assert_eq!(dump_tokens(&mut st), ~[~"SG($:var)", ~"SG(@:attr)", ~"SG(#:tag)", ~"SG(%:macro)",
+                                       ~"S($)", ~"W(x)", ~"S($)", ~"S(()", ~"W(cmd)", ~"S())",
+                                       ~"S(@)", ~"N(1)", ~"SG(#:été)", ~"W(a)", ~"SG($:b)"]);
+
+    st.set_profile(ShellProfile);
+    st.set_datas_with_str(~"echo $1 $10 $2x $HOME $_");
+    assert_eq!(dump_tokens(&mut st), ~[~"W(echo)", ~"SG($:1)", ~"SG($:1)", ~"N(0)", ~"SG($:2)",
+                                       ~"W(x)", ~"SG($:HOME)", ~"SG($:_)"]);
+}
+
+#[test]
+fn test_lisp_profile() -> () {
+    let mut st = StringTokenizer::new_with_profile(
+        ~"(defn nil? [x] (set! *out* (- x 1))) ; done\n(foo-bar --x -1 'a nil)\n(+ -2.5 x)",
+        LispProfile);
+    assert_eq!(dump_tokens(&mut st),
+               ~[~"S(()", ~"K(defn)", ~"W(nil?)", ~"S([)", ~"W(x)", ~"S(])", ~"S(()",
+                 ~"K(set!)", ~"W(*out*)", ~"S(()", ~"S(-)", ~"W(x)", ~"N(1)", ~"S())",
+                 ~"S())", ~"S())", ~"S(()", ~"W(foo-bar)", ~"W(--x)", ~"N(-1)",
+                 ~"S(')", ~"W(a)", ~"NU(nil)", ~"S())", ~"S(()", ~"S(+)", ~"N(-2.5)", ~"W(x)",
+                 ~"S())"]);
+}
+
+#[cfg(test)]
+mod library {
+    pub use super::StringTokenizer;
+    pub use super::KeyWord;
+    pub use super::Operator;
+    pub use super::Custom;
+    pub use super::OtherKeyword;
+}
+
+#[cfg(test)]
+mod declared {
+    tokenizer!(mod calc use library {
+        keywords { Let => "let", Print => "print" }
+        operators { EqEq => "==", Assign => "=" }
+        rules { Int => "[0-9]+", Ident => "[a-z_]+" }
+    })
+}
+
+#[test]
+fn test_tokenizer_macro() -> () {
+    let mut st = declared::calc::new(~"let x = 42; print x == 1");
+    let mut tokens = ~[];
+
+    while st.has_token() {
+        tokens.push(declared::calc::token(&st));
     }
+    assert_eq!(tokens, ~[declared::calc::Let, declared::calc::Ident, declared::calc::Assign,
+                         declared::calc::Int, declared::calc::Other, declared::calc::Print,
+                         declared::calc::Ident, declared::calc::EqEq, declared::calc::Int]);
+    assert_eq!(declared::calc::Let as uint, 0);
 }